@@ -1,11 +1,17 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
+use crate::config::github_config::IfExists;
+use crate::utils::context::OverwritePolicy;
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Enable debug output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub debug: bool,
 
     /// Path to the configuration file (YAML or JSON)
@@ -20,11 +26,134 @@ pub struct Args {
     #[arg(short = 'n', long)]
     pub template: Option<String>,
 
-    /// Trigger GitHub workflow instead of local generation
+    /// Create a repository on the configured provider (GitHub by default) instead of local generation
     #[arg(long)]
     pub remote: bool,
 
-    /// GitHub token for remote workflow
-    #[arg(long)]
+    /// Provider token for remote workflow [default: GITHUB_TOKEN, GITLAB_TOKEN or GITEA_TOKEN]
+    #[arg(long, global = true)]
     pub token: Option<String>,
+
+    /// Print what remote mode would create on the provider, without generating or creating anything
+    #[arg(long, requires = "remote")]
+    pub dry_run: bool,
+
+    /// What to do when the repository already exists [default: repository.if_exists or abort]
+    #[arg(long, requires = "remote", value_name = "MODE")]
+    pub if_exists: Option<IfExists>,
+
+    /// Delete the created repository when pushing the generated code fails
+    #[arg(long, requires = "remote")]
+    pub delete_on_failure: bool,
+
+    /// Overwrite existing files at the project path
+    #[arg(long, global = true, conflicts_with_all = ["skip_existing", "prompt_conflicts"])]
+    pub force: bool,
+
+    /// Keep existing files at the project path and only add new ones
+    #[arg(long, global = true, conflicts_with = "prompt_conflicts")]
+    pub skip_existing: bool,
+
+    /// Show a diff and ask what to do for each file that already exists
+    #[arg(long, global = true)]
+    pub prompt_conflicts: bool,
+
+    /// Fail on unresolved placeholders, missing replacements and unused variables
+    #[arg(long, global = true)]
+    pub strict: bool,
+
+    /// Do not install dependencies after generation
+    #[arg(long, global = true, conflicts_with = "install_command")]
+    pub skip_install: bool,
+
+    /// Command used to install dependencies instead of the detected one (e.g. "npm ci")
+    #[arg(long, global = true, value_name = "COMMAND")]
+    pub install_command: Option<String>,
+
+    /// Do not run the pre- and post-generation hooks declared by the template
+    #[arg(long, global = true, conflicts_with = "trust_hooks")]
+    pub no_hooks: bool,
+
+    /// Run the template hooks without asking for confirmation, even if the template is not trusted
+    #[arg(long, global = true)]
+    pub trust_hooks: bool,
+
+    /// Seconds after which dependency installation is aborted, 0 for no limit [default: 900]
+    #[arg(long, global = true, value_name = "SECONDS")]
+    pub install_timeout: Option<u64>,
+
+    /// Do not print the dependency installation output, write it to a log file instead
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// File receiving the dependency installation output (default: a file in the temp directory)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub install_log: Option<PathBuf>,
+
+    /// Initialize a git repository with an initial commit in the generated project (local mode)
+    #[arg(long, global = true)]
+    pub git_init: bool,
+
+    /// Keep the staging directory when generation fails (for debugging)
+    #[arg(long, global = true)]
+    pub keep_staging: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Update a generated project to a newer template version with a three-way merge
+    Update {
+        /// Directory of the project to update (must contain a generation manifest)
+        project_dir: PathBuf,
+
+        /// Template branch, tag or commit to update to (default: the recorded branch)
+        #[arg(long = "ref")]
+        reference: Option<String>,
+    },
+    /// Generate a new project from the manifest of an existing one, without prompting
+    Regenerate {
+        /// Directory of the project to regenerate (must contain a generation manifest)
+        project_dir: PathBuf,
+
+        /// Directory to generate into (default: <PROJECT_DIR>-regenerated)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Template branch, tag or commit to use (default: the recorded commit)
+        #[arg(long = "ref")]
+        reference: Option<String>,
+
+        /// Override a recorded variable, can be repeated
+        #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_variable)]
+        variables: Vec<(String, String)>,
+    },
+    /// Push a project kept after a failed remote push to its repository and finish the setup
+    RetryPush {
+        /// Project directory printed when the push failed
+        project_dir: PathBuf,
+    },
+}
+
+fn parse_variable(input: &str) -> Result<(String, String), String> {
+    let (name, value) = input
+        .split_once('=')
+        .ok_or_else(|| format!("invalid variable '{}', expected NAME=VALUE", input))?;
+    if name.is_empty() {
+        return Err(format!("invalid variable '{}', the name is empty", input));
+    }
+    Ok((name.to_string(), value.to_string()))
+}
+
+impl Args {
+    pub fn overwrite_policy(&self) -> OverwritePolicy {
+        if self.force {
+            OverwritePolicy::Force
+        } else if self.skip_existing {
+            OverwritePolicy::SkipExisting
+        } else if self.prompt_conflicts {
+            OverwritePolicy::Prompt
+        } else {
+            OverwritePolicy::Refuse
+        }
+    }
 }
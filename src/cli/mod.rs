@@ -11,7 +11,7 @@ use crate::generate::project_generator;
 use crate::template::TemplateManager;
 use crate::utils::{context, strings};
 
-pub use functions::prompt_for_repo_name;
+pub use functions::{prompt_for_repo_name, prompt_for_variable};
 
 pub fn get_template_info(
     args: &Args,
@@ -25,6 +25,11 @@ pub fn get_template_info(
         // Set variables from config
         context::set_variables(config.to_variables());
 
+        let mut options = context::get_options();
+        options.hook_environment = config.hook_environment.clone();
+        options.git = config.git.clone();
+        context::set_options(options);
+
         // Get template info from config
         config.get_template_info().ok_or_else(|| {
             Error::new(
@@ -38,7 +43,7 @@ pub fn get_template_info(
         // List available templates
         let templates = template_manager
             .list_templates()
-            .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to list templates: {}", e)))?;
+            .map_err(|e| Error::other(format!("Failed to list templates: {}", e)))?;
 
         // Select template
         functions::select_template(templates)
@@ -91,11 +96,8 @@ pub fn interact(template_path: &Path) -> Result<()> {
         template_path.file_name().unwrap().to_string_lossy()
     );
 
-    project_generator::generate_project(template_path, &project_path)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("An error occurred while generating the project: {}", e)))?;
-
-    project_generator::install_dependencies(&project_path)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("An error occurred while installing dependencies: {}", e)))?;
+    project_generator::generate_project(template_path, &project_path, true)
+        .map_err(|e| Error::other(format!("An error occurred while generating the project: {}", e)))?;
 
     println!("Project generated successfully");
     Ok(())
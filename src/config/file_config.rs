@@ -2,6 +2,11 @@ use std::io::{Error, ErrorKind, Result};
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use crate::config::github_config::{
+    BranchProtection, CollaboratorAccess, RepositorySettings, SecretDefinition, TeamAccess, VariableDefinition,
+};
+use crate::config::provider_config::{LabelDefinition, ProviderKind, ServerSettings};
+use crate::config::GitSettings;
 use crate::utils::context;
 
 #[derive(serde::Deserialize)]
@@ -16,12 +21,54 @@ pub struct FileConfig {
     pub template_name: Option<String>,
     #[serde(default)]
     pub template_branch: Option<String>,
+    /// Single topic, superseded by `github_topics`.
     #[serde(default)]
     pub github_tag: Option<String>,
+    #[serde(default)]
+    pub github_topics: Vec<String>,
+    /// Issue labels created in the repository, on every provider.
+    #[serde(default)]
+    pub labels: Vec<LabelDefinition>,
+    /// Hosting service of remote mode, GitHub by default.
+    #[serde(default)]
+    pub provider: ProviderKind,
+    /// Self-hosted instance to use instead of github.com or gitlab.com, `github_server` in
+    /// older configuration files.
+    #[serde(default, alias = "github_server")]
+    pub server: ServerSettings,
+    /// Settings of the repository on every provider, `github_repository` in older
+    /// configuration files.
+    #[serde(default, alias = "github_repository")]
+    pub repository: RepositorySettings,
+    #[serde(default)]
+    pub github_branch_protection: Option<BranchProtection>,
+    #[serde(default)]
+    pub github_teams: Vec<TeamAccess>,
+    #[serde(default)]
+    pub github_collaborators: Vec<CollaboratorAccess>,
+    #[serde(default)]
+    pub github_secrets: Vec<SecretDefinition>,
+    #[serde(default)]
+    pub github_variables: Vec<VariableDefinition>,
+    #[serde(default)]
+    pub hook_environment: HookEnvironment,
+    #[serde(default)]
+    pub git: GitSettings,
     #[serde(flatten)]
     pub additional_vars: std::collections::HashMap<String, String>,
 }
 
+/// Environment given to template hooks.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub struct HookEnvironment {
+    /// Start hooks from an empty environment, keeping only `HOOK_BASE_ENVIRONMENT` and `allow`.
+    #[serde(default)]
+    pub clear: bool,
+    /// Variables passed to hooks, including those stripped by default (e.g. `GITHUB_TOKEN`).
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
 impl FileConfig {
     pub fn get_template_info(&self) -> Option<(String, String)> {
         match (&self.template_category, &self.template_name) {
@@ -40,25 +87,9 @@ impl FileConfig {
         self.template_branch.as_deref().unwrap_or("main")
     }
 
-    pub fn get_github_tag(&self) -> Option<&String> {
-        self.github_tag.as_ref()
-    }
-
-    pub fn validate_github_tag(&self) -> Result<()> {
-        if let Some(tag) = &self.github_tag {
-            let valid_tags = ["apps", "packages", "utils"];
-            if !valid_tags.contains(&tag.as_str()) {
-                let error_msg = format!(
-                    "Invalid github_tag '{}'. Allowed values are: {}",
-                    tag,
-                    valid_tags.join(", ")
-                );
-                context::debug_print(&format!("ERROR: {}", error_msg));
-                return Err(Error::new(ErrorKind::InvalidData, error_msg));
-            }
-            context::debug_print(&format!("Valid github_tag found: '{}'", tag));
-        }
-        Ok(())
+    /// Topics from `github_topics`, plus `github_tag` kept for older configuration files.
+    pub fn get_github_topics(&self) -> Vec<String> {
+        self.github_tag.iter().chain(&self.github_topics).cloned().collect()
     }
 
     pub fn to_variables(&self) -> std::collections::HashMap<String, String> {
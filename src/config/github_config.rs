@@ -0,0 +1,504 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map, Value};
+
+use crate::config::{MAX_GITHUB_TOPICS, MAX_GITHUB_TOPIC_LENGTH};
+use crate::utils::context;
+
+/// What remote mode does when the repository already exists.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+#[value(rename_all = "snake_case")]
+pub enum IfExists {
+    /// Stop before generating anything.
+    #[default]
+    Abort,
+    /// Push to the repository if it has no commits yet.
+    Reuse,
+    /// Push the generated code to a new branch and open a pull request.
+    PullRequest,
+}
+
+impl fmt::Display for IfExists {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IfExists::Abort => "abort",
+            IfExists::Reuse => "reuse",
+            IfExists::PullRequest => "pull_request",
+        })
+    }
+}
+
+/// Who can see a created repository.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    #[default]
+    Public,
+    Private,
+    /// Visible to members of the enterprise, or to signed-in users on GitLab.
+    Internal,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Visibility::Public => "public",
+            Visibility::Private => "private",
+            Visibility::Internal => "internal",
+        })
+    }
+}
+
+/// Settings of the created repository, from `repository` in the configuration file.
+/// Settings left unset keep the organization defaults.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct RepositorySettings {
+    #[serde(default)]
+    pub visibility: Visibility,
+    /// Branch the code is pushed to and set as default, `git.default_branch` when omitted.
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub allow_merge_commit: Option<bool>,
+    #[serde(default)]
+    pub allow_squash_merge: Option<bool>,
+    #[serde(default)]
+    pub allow_rebase_merge: Option<bool>,
+    /// Delete head branches once their pull request is merged.
+    #[serde(default)]
+    pub delete_branch_on_merge: Option<bool>,
+    #[serde(default)]
+    pub has_issues: Option<bool>,
+    #[serde(default)]
+    pub has_wiki: Option<bool>,
+    #[serde(default)]
+    pub has_projects: Option<bool>,
+    /// What to do when the repository already exists, abort by default.
+    #[serde(default)]
+    pub if_exists: IfExists,
+    /// Delete the repository created by the run when pushing the code to it fails.
+    #[serde(default)]
+    pub delete_on_push_failure: bool,
+}
+
+impl RepositorySettings {
+    pub fn validate(&self) -> Result<()> {
+        let strategies = [self.allow_merge_commit, self.allow_squash_merge, self.allow_rebase_merge];
+        if strategies.iter().all(|allowed| *allowed == Some(false)) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "repository must allow at least one of merge commits, squash merging or rebase merging",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Toggles sent when creating the repository, in the GitHub API field names.
+    pub fn toggles(&self) -> Vec<(&'static str, bool)> {
+        [
+            ("allow_merge_commit", self.allow_merge_commit),
+            ("allow_squash_merge", self.allow_squash_merge),
+            ("allow_rebase_merge", self.allow_rebase_merge),
+            ("delete_branch_on_merge", self.delete_branch_on_merge),
+            ("has_issues", self.has_issues),
+            ("has_wiki", self.has_wiki),
+            ("has_projects", self.has_projects),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| value.map(|value| (name, value)))
+        .collect()
+    }
+
+    /// Fields of the repository creation request.
+    pub fn creation_fields(&self) -> Map<String, Value> {
+        let mut fields = Map::new();
+        fields.insert("visibility".to_string(), Value::from(self.visibility.to_string()));
+        for (name, value) in self.toggles() {
+            fields.insert(name.to_string(), Value::from(value));
+        }
+        fields
+    }
+}
+
+/// Combines the template category, the template topics and the configured topics, in that
+/// order and without duplicates, and checks them against GitHub's topic rules.
+pub fn combine_topics(category: &str, template_topics: &[String], user_topics: &[String]) -> Result<Vec<String>> {
+    let mut topics: Vec<String> = Vec::new();
+    for topic in std::iter::once(category).chain(template_topics.iter().chain(user_topics).map(String::as_str)) {
+        validate_topic(topic)?;
+        if !topics.iter().any(|t| t == topic) {
+            topics.push(topic.to_string());
+        }
+    }
+
+    if topics.len() > MAX_GITHUB_TOPICS {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("A repository can have at most {} topics, {} found: {}", MAX_GITHUB_TOPICS, topics.len(), topics.join(", ")),
+        ));
+    }
+    context::debug_print(&format!("Repository topics: {:?}", topics));
+    Ok(topics)
+}
+
+/// Topics are lowercase letters, numbers and hyphens, start with a letter or a number and
+/// are at most `MAX_GITHUB_TOPIC_LENGTH` characters long.
+fn validate_topic(topic: &str) -> Result<()> {
+    let problem = if topic.is_empty() {
+        Some("it is empty".to_string())
+    } else if topic.len() > MAX_GITHUB_TOPIC_LENGTH {
+        Some(format!("it is longer than {} characters", MAX_GITHUB_TOPIC_LENGTH))
+    } else if topic.starts_with('-') {
+        Some("it must start with a letter or a number".to_string())
+    } else if !topic.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        Some("only lowercase letters, numbers and hyphens are allowed".to_string())
+    } else {
+        None
+    };
+
+    match problem {
+        Some(problem) => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid GitHub topic '{}': {}", topic, problem),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Protection applied to the default branch after the first push, from
+/// `github_branch_protection` in the configuration file or `branch_protection` in the
+/// template metadata.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct BranchProtection {
+    /// Protected branch, the default branch when omitted.
+    #[serde(default)]
+    pub branch: Option<String>,
+    /// Approvals required before merging a pull request, reviews are not required when 0.
+    #[serde(default)]
+    pub required_approving_reviews: u8,
+    #[serde(default)]
+    pub dismiss_stale_reviews: bool,
+    #[serde(default)]
+    pub require_code_owner_reviews: bool,
+    /// Status check contexts that must pass before merging.
+    #[serde(default)]
+    pub required_status_checks: Vec<String>,
+    /// Require branches to be up to date with the protected branch before merging.
+    #[serde(default)]
+    pub strict_status_checks: bool,
+    /// Apply the rules to administrators too.
+    #[serde(default)]
+    pub enforce_admins: bool,
+    #[serde(default)]
+    pub allow_force_pushes: bool,
+    #[serde(default)]
+    pub allow_deletions: bool,
+    #[serde(default)]
+    pub required_linear_history: bool,
+}
+
+impl BranchProtection {
+    pub fn validate(&self) -> Result<()> {
+        if self.required_approving_reviews > 6 {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "required_approving_reviews must be between 0 and 6",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Body of the branch protection request, null fields disable the matching rule.
+    pub fn request_body(&self) -> Value {
+        let status_checks = (!self.required_status_checks.is_empty()).then(|| {
+            json!({
+                "strict": self.strict_status_checks,
+                "contexts": self.required_status_checks,
+            })
+        });
+        let reviews = (self.required_approving_reviews > 0).then(|| {
+            json!({
+                "required_approving_review_count": self.required_approving_reviews,
+                "dismiss_stale_reviews": self.dismiss_stale_reviews,
+                "require_code_owner_reviews": self.require_code_owner_reviews,
+            })
+        });
+
+        json!({
+            "required_status_checks": status_checks,
+            "enforce_admins": self.enforce_admins,
+            "required_pull_request_reviews": reviews,
+            "restrictions": null,
+            "required_linear_history": self.required_linear_history,
+            "allow_force_pushes": self.allow_force_pushes,
+            "allow_deletions": self.allow_deletions,
+        })
+    }
+
+    /// Human-readable list of the rules, for the plan and the final report.
+    pub fn describe(&self) -> Vec<String> {
+        let mut rules = Vec::new();
+        if self.required_approving_reviews > 0 {
+            let mut rule = format!("{} approving review(s) required", self.required_approving_reviews);
+            if self.dismiss_stale_reviews {
+                rule.push_str(", stale reviews dismissed");
+            }
+            if self.require_code_owner_reviews {
+                rule.push_str(", code owner review required");
+            }
+            rules.push(rule);
+        }
+        if !self.required_status_checks.is_empty() {
+            rules.push(format!(
+                "status checks required: {}{}",
+                self.required_status_checks.join(", "),
+                if self.strict_status_checks { " (branch must be up to date)" } else { "" }
+            ));
+        }
+        if self.required_linear_history {
+            rules.push("linear history required".to_string());
+        }
+        rules.push(format!("force pushes {}", if self.allow_force_pushes { "allowed" } else { "blocked" }));
+        rules.push(format!("branch deletion {}", if self.allow_deletions { "allowed" } else { "blocked" }));
+        if self.enforce_admins {
+            rules.push("rules enforced for administrators".to_string());
+        }
+        rules
+    }
+}
+
+/// Repository permission granted to a team or a collaborator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    Pull,
+    Triage,
+    Push,
+    Maintain,
+    Admin,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Permission::Pull => "pull",
+            Permission::Triage => "triage",
+            Permission::Push => "push",
+            Permission::Maintain => "maintain",
+            Permission::Admin => "admin",
+        })
+    }
+}
+
+/// Organization team given access to the created repository, from `github_teams`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct TeamAccess {
+    pub slug: String,
+    pub permission: Permission,
+    /// CODEOWNERS patterns owned by the team (e.g. "*", "/docs/").
+    #[serde(default)]
+    pub owns: Vec<String>,
+}
+
+/// User given access to the created repository, from `github_collaborators`.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct CollaboratorAccess {
+    pub username: String,
+    pub permission: Permission,
+    /// CODEOWNERS patterns owned by the user.
+    #[serde(default)]
+    pub owns: Vec<String>,
+}
+
+pub fn validate_access(teams: &[TeamAccess], collaborators: &[CollaboratorAccess]) -> Result<()> {
+    let valid = |name: &str, extra: &[char]| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || extra.contains(&c))
+    };
+    if let Some(team) = teams.iter().find(|team| !valid(&team.slug, &['_'])) {
+        return Err(Error::new(ErrorKind::InvalidData, format!("Invalid team slug '{}'", team.slug)));
+    }
+    if let Some(user) = collaborators.iter().find(|user| !valid(&user.username, &[])) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid GitHub username '{}' (write it without '@')", user.username),
+        ));
+    }
+    Ok(())
+}
+
+/// Content of a CODEOWNERS file built from the `owns` patterns, `None` when nobody owns anything.
+/// Patterns keep the order they first appear in, teams are listed before users.
+pub fn codeowners_content(organization: &str, teams: &[TeamAccess], collaborators: &[CollaboratorAccess]) -> Option<String> {
+    let owners = teams
+        .iter()
+        .map(|team| (format!("@{}/{}", organization, team.slug), &team.owns))
+        .chain(collaborators.iter().map(|user| (format!("@{}", user.username), &user.owns)));
+
+    let mut rules: Vec<(String, Vec<String>)> = Vec::new();
+    for (owner, patterns) in owners {
+        for pattern in patterns {
+            match rules.iter_mut().find(|(p, _)| p == pattern) {
+                Some((_, pattern_owners)) => pattern_owners.push(owner.clone()),
+                None => rules.push((pattern.clone(), vec![owner.clone()])),
+            }
+        }
+    }
+    if rules.is_empty() {
+        return None;
+    }
+
+    let mut content = String::from("# Generated by project-generator from github_teams and github_collaborators\n");
+    for (pattern, pattern_owners) in rules {
+        content.push_str(&format!("{} {}\n", pattern, pattern_owners.join(" ")));
+    }
+    Some(content)
+}
+
+/// Actions secret of the created repository, from `github_secrets`. Values are read from an
+/// environment variable or a file, never written in the configuration file.
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct SecretDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub from_env: Option<String>,
+    #[serde(default)]
+    pub from_file: Option<PathBuf>,
+    /// Keep the line break ending a `from_file` value, e.g. for private keys.
+    #[serde(default)]
+    pub keep_trailing_newline: bool,
+    /// Only read to reject plain text secrets with a clear message.
+    #[serde(default)]
+    value: Option<serde_yaml::Value>,
+}
+
+/// Actions variable of the created repository, from `github_variables`.
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct VariableDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub from_env: Option<String>,
+    #[serde(default)]
+    pub from_file: Option<PathBuf>,
+    #[serde(default)]
+    pub keep_trailing_newline: bool,
+}
+
+/// A secret or variable with its value, resolved before anything is generated.
+pub struct ResolvedValue {
+    pub name: String,
+    /// Where the value comes from, shown instead of secret values.
+    pub source: String,
+    pub value: String,
+}
+
+impl SecretDefinition {
+    pub fn resolve(&self) -> Result<ResolvedValue> {
+        validate_actions_name("secret", &self.name)?;
+        if self.value.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Secret '{}' has a plain text value, read it from an environment variable (from_env) or a file (from_file) instead",
+                    self.name
+                ),
+            ));
+        }
+        resolve_value(
+            "secret",
+            &self.name,
+            None,
+            self.from_env.as_deref(),
+            self.from_file.as_deref(),
+            self.keep_trailing_newline,
+        )
+    }
+}
+
+impl VariableDefinition {
+    pub fn resolve(&self) -> Result<ResolvedValue> {
+        validate_actions_name("variable", &self.name)?;
+        resolve_value(
+            "variable",
+            &self.name,
+            self.value.as_deref(),
+            self.from_env.as_deref(),
+            self.from_file.as_deref(),
+            self.keep_trailing_newline,
+        )
+    }
+}
+
+fn resolve_value(
+    kind: &str,
+    name: &str,
+    value: Option<&str>,
+    from_env: Option<&str>,
+    from_file: Option<&Path>,
+    keep_trailing_newline: bool,
+) -> Result<ResolvedValue> {
+    let resolved = |source: String, value: String| ResolvedValue { name: name.to_string(), source, value };
+
+    match (value, from_env, from_file) {
+        (Some(value), None, None) => Ok(resolved("configuration file".to_string(), value.to_string())),
+        (None, Some(variable), None) => {
+            let value = env::var(variable).map_err(|_| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!("Environment variable '{}' for {} '{}' is not set", variable, kind, name),
+                )
+            })?;
+            Ok(resolved(format!("environment variable {}", variable), value))
+        }
+        (None, None, Some(path)) => {
+            let path = expand_home(path);
+            let mut value = fs::read_to_string(&path).map_err(|e| {
+                Error::new(
+                    e.kind(),
+                    format!("Failed to read file '{}' for {} '{}': {}", path.display(), kind, name, e),
+                )
+            })?;
+            // Editors end files with a line break that is not part of a token
+            if !keep_trailing_newline {
+                let trimmed = value.strip_suffix("\r\n").or_else(|| value.strip_suffix('\n')).unwrap_or(&value);
+                value.truncate(trimmed.len());
+            }
+            Ok(resolved(format!("file {}", path.display()), value))
+        }
+        _ => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("The {} '{}' needs exactly one value source", kind, name),
+        )),
+    }
+}
+
+/// Names are letters, digits and underscores, do not start with a digit or `GITHUB_`.
+fn validate_actions_name(kind: &str, name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && !name.to_ascii_uppercase().starts_with("GITHUB_")
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Invalid {} name '{}': use letters, digits and underscores, not starting with a digit or GITHUB_",
+                kind, name
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
@@ -1,16 +1,91 @@
 pub mod file_config;
+pub mod github_config;
+pub mod provider_config;
+
+use github_config::BranchProtection;
 
 pub const PACKAGE_ROOT_PATH: &str = env!("CARGO_MANIFEST_DIR");
 pub const CREATION_PATH: &str = "../";
 pub const REPO_URL: &str = "https://github.com/NextNodeSolutions";
+/// Public instances of the hosting providers, used unless `server` points elsewhere.
+pub const DEFAULT_GITHUB_URL: &str = "https://github.com";
+pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+pub const DEFAULT_GITLAB_URL: &str = "https://gitlab.com";
 
 pub const TEMPLATE_REPO_URL: &str = "/utils_project-templates.git";
 pub const TEMPLATE_BRANCH: &str = "main";
 pub const TEMPLATE_CATEGORIES: &[&str] = &["apps", "packages", "utils"];
 
 pub const TEMPLATE_CONFIG_FILE: &str = "template_config.json";
+pub const TEMPLATE_METADATA_FILE: &str = "template_metadata.json";
+pub const MANIFEST_FILE: &str = ".project-generator.json";
+/// Variables whose name contains one of these markers are never written to the manifest.
+pub const SECRET_VARIABLE_MARKERS: &[&str] = &["secret", "token", "password"];
+/// Variables consumed by the generator itself, never reported as unused by strict mode.
+pub const GENERATOR_VARIABLES: &[&str] = &["project_name", "name", "description"];
+/// Gitignore-syntax file in a template listing what is not copied into generated projects.
+pub const TEMPLATE_IGNORE_FILE: &str = ".templateignore";
+/// Directories excluded when a template ships no `TEMPLATE_IGNORE_FILE`.
 pub const EXCLUDED_DIRS: &[&str] = &["node_modules", ".next", ".turbo", "dist", "build", "out"];
-pub const EXCLUDED_FILES: &[&str] = &[TEMPLATE_CONFIG_FILE];
+/// Default dependency installation timeout, in seconds.
+pub const DEFAULT_INSTALL_TIMEOUT_SECS: u64 = 900;
+/// Number of output lines included in the error when installation fails.
+pub const INSTALL_ERROR_TAIL_LINES: usize = 20;
+/// GitHub limits on repository topics.
+pub const MAX_GITHUB_TOPICS: usize = 20;
+pub const MAX_GITHUB_TOPIC_LENGTH: usize = 50;
+/// GitHub limits on issue labels, the strictest of the providers.
+pub const MAX_LABEL_NAME_LENGTH: usize = 50;
+pub const MAX_LABEL_DESCRIPTION_LENGTH: usize = 100;
+/// Color of labels that do not set one.
+pub const DEFAULT_LABEL_COLOR: &str = "ededed";
+/// Location of the CODEOWNERS file generated from the configured teams and collaborators.
+pub const CODEOWNERS_FILE: &str = ".github/CODEOWNERS";
+/// Git defaults used when neither the configuration file nor the template sets them.
+pub const DEFAULT_GIT_BRANCH: &str = "main";
+pub const DEFAULT_GIT_AUTHOR_NAME: &str = "Project Generator";
+pub const DEFAULT_GIT_AUTHOR_EMAIL: &str = "generator@nextnode.dev";
+pub const DEFAULT_GIT_COMMIT_MESSAGE: &str = "first commit";
+/// Timeout applied to template hooks that do not declare one, in seconds.
+pub const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 300;
+/// Prefix of the environment variables exposing template variables to hooks.
+pub const HOOK_VARIABLE_PREFIX: &str = "PG_VAR_";
+/// Variables kept when the hook environment is cleared.
+pub const HOOK_BASE_ENVIRONMENT: &[&str] = &["PATH", "HOME", "USER", "LANG", "TERM", "TMPDIR", "TEMP", "TMP", "SYSTEMROOT"];
+/// Credentials never inherited by hooks unless explicitly allowed.
+pub const HOOK_STRIPPED_ENVIRONMENT: &[&str] = &[
+    "GITHUB_TOKEN",
+    "GH_TOKEN",
+    "GH_ENTERPRISE_TOKEN",
+    "GITHUB_ENTERPRISE_TOKEN",
+    "GITLAB_TOKEN",
+    "GITEA_TOKEN",
+];
+/// File of the user configuration directory recording trusted template commits.
+pub const TRUSTED_TEMPLATES_FILE: &str = "project-generator/trusted_templates.json";
+/// Retries of a hosting provider API call failing with a network error, a server error or a
+/// rate limit.
+pub const API_MAX_RETRIES: u32 = 3;
+/// First retry delay, doubled on every retry.
+pub const API_RETRY_BASE_DELAY_MS: u64 = 1000;
+/// Longest rate limit wait before giving up instead of retrying.
+pub const API_MAX_RETRY_WAIT_SECS: u64 = 60;
+/// Timeout of a single API request, including reading the response.
+pub const API_REQUEST_TIMEOUT_SECS: u64 = 30;
+/// Prefix of the branch the generated code is pushed to when the repository already has commits.
+pub const PULL_REQUEST_BRANCH_PREFIX: &str = "project-generator/";
+/// File left in a kept remote project when the push failed, read by `retry-push`.
+pub const PUSH_STATE_FILE: &str = ".project-generator-push.json";
+/// Placeholder file that keeps an otherwise empty directory in the template repository.
+/// The directory is created in the generated project, the placeholder itself is not copied.
+pub const EMPTY_DIR_PLACEHOLDER: &str = ".templatekeep";
+/// Generator files that are never copied into generated projects.
+pub const EXCLUDED_FILES: &[&str] = &[
+    TEMPLATE_CONFIG_FILE,
+    TEMPLATE_METADATA_FILE,
+    TEMPLATE_IGNORE_FILE,
+    EMPTY_DIR_PLACEHOLDER,
+];
 
 #[derive(Debug, serde::Deserialize)]
 pub struct Replacement {
@@ -20,6 +95,9 @@ pub struct Replacement {
     pub value: String,
     #[serde(rename = "type", default = "default_type")]
     pub type_: String,
+    /// Secret values are never recorded in the generation manifest.
+    #[serde(default)]
+    pub secret: bool,
 }
 
 fn default_type() -> String {
@@ -30,6 +108,116 @@ fn default_type() -> String {
 pub struct TemplateConfig {
     pub files_to_replace: Vec<String>,
     pub replacements: Vec<Replacement>,
+    /// Encoding of the files to replace (e.g. "utf-16le", "latin1"), detected when omitted.
+    #[serde(default)]
+    pub encoding: Option<String>,
 }
 
 pub type TemplateJson = Vec<TemplateConfig>;
+
+/// Optional template-level settings, read from `TEMPLATE_METADATA_FILE`.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct TemplateMetadata {
+    /// Package manager to install dependencies with (pnpm, npm, yarn, bun, cargo, poetry, go).
+    #[serde(default)]
+    pub package_manager: Option<String>,
+    /// Full install command, takes precedence over `package_manager`.
+    #[serde(default)]
+    pub install_command: Option<String>,
+    /// Commands run before and after generation.
+    #[serde(default)]
+    pub hooks: TemplateHooks,
+    /// Git defaults for generated projects, overridden by the configuration file.
+    #[serde(default)]
+    pub git: GitSettings,
+    /// Topics added to repositories created from the template.
+    #[serde(default)]
+    pub topics: Vec<String>,
+    /// Protection of the default branch of repositories created from the template,
+    /// replaced by the one of the configuration file.
+    #[serde(default)]
+    pub branch_protection: Option<BranchProtection>,
+}
+
+/// Git repository settings, from the configuration file or the template metadata.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct GitSettings {
+    /// Initialize a repository in locally generated projects.
+    #[serde(default)]
+    pub init: Option<bool>,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub author_name: Option<String>,
+    #[serde(default)]
+    pub author_email: Option<String>,
+    #[serde(default)]
+    pub commit_message: Option<String>,
+}
+
+impl GitSettings {
+    /// Fills the settings left unset with those of `fallback`.
+    pub fn or(&self, fallback: &GitSettings) -> GitSettings {
+        GitSettings {
+            init: self.init.or(fallback.init),
+            default_branch: self.default_branch.clone().or_else(|| fallback.default_branch.clone()),
+            author_name: self.author_name.clone().or_else(|| fallback.author_name.clone()),
+            author_email: self.author_email.clone().or_else(|| fallback.author_email.clone()),
+            commit_message: self.commit_message.clone().or_else(|| fallback.commit_message.clone()),
+        }
+    }
+
+    pub fn default_branch(&self) -> &str {
+        self.default_branch.as_deref().unwrap_or(DEFAULT_GIT_BRANCH)
+    }
+
+    pub fn author_name(&self) -> &str {
+        self.author_name.as_deref().unwrap_or(DEFAULT_GIT_AUTHOR_NAME)
+    }
+
+    pub fn author_email(&self) -> &str {
+        self.author_email.as_deref().unwrap_or(DEFAULT_GIT_AUTHOR_EMAIL)
+    }
+
+    pub fn commit_message(&self) -> &str {
+        self.commit_message.as_deref().unwrap_or(DEFAULT_GIT_COMMIT_MESSAGE)
+    }
+}
+
+/// Hooks declared by a template, run in order in the project directory.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct TemplateHooks {
+    /// Run in the empty staging directory, before the template is copied.
+    #[serde(default)]
+    pub pre_generate: Vec<Hook>,
+    /// Run in the project directory once it is generated and its dependencies are installed.
+    #[serde(default)]
+    pub post_generate: Vec<Hook>,
+}
+
+impl TemplateHooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre_generate.is_empty() && self.post_generate.is_empty()
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct Hook {
+    /// Label shown while the hook runs, the command is shown when omitted.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Shell command line.
+    pub command: String,
+    /// Seconds after which the hook is killed, `DEFAULT_HOOK_TIMEOUT_SECS` when omitted.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Carry on with generation when the hook fails or times out.
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+impl Hook {
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.command)
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+
+use crate::config::{
+    DEFAULT_GITHUB_API_URL, DEFAULT_GITHUB_URL, DEFAULT_GITLAB_URL, DEFAULT_LABEL_COLOR, MAX_LABEL_DESCRIPTION_LENGTH,
+    MAX_LABEL_NAME_LENGTH,
+};
+
+/// Hosting service the repositories are created on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    #[default]
+    GitHub,
+    GitLab,
+    /// Gitea and its fork Forgejo, which share the same API.
+    #[serde(alias = "forgejo")]
+    Gitea,
+}
+
+impl ProviderKind {
+    /// Public instance used when no server URL is configured.
+    pub fn default_url(&self) -> Option<&'static str> {
+        match self {
+            ProviderKind::GitHub => Some(DEFAULT_GITHUB_URL),
+            ProviderKind::GitLab => Some(DEFAULT_GITLAB_URL),
+            ProviderKind::Gitea => None,
+        }
+    }
+
+    /// Path of the REST API on a self-hosted instance.
+    fn api_path(&self) -> &'static str {
+        match self {
+            ProviderKind::GitHub => "/api/v3",
+            ProviderKind::GitLab => "/api/v4",
+            ProviderKind::Gitea => "/api/v1",
+        }
+    }
+
+    /// Environment variable the token is read from when `--token` is not given.
+    pub fn token_variable(&self) -> &'static str {
+        match self {
+            ProviderKind::GitHub => "GITHUB_TOKEN",
+            ProviderKind::GitLab => "GITLAB_TOKEN",
+            ProviderKind::Gitea => "GITEA_TOKEN",
+        }
+    }
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ProviderKind::GitHub => "GitHub",
+            ProviderKind::GitLab => "GitLab",
+            ProviderKind::Gitea => "Gitea",
+        })
+    }
+}
+
+/// Instance of the hosting provider remote mode works with, the public one (github.com or
+/// gitlab.com) unless a self-hosted server is configured.
+#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub struct ServerSettings {
+    /// Web and git URL of the instance, e.g. `https://github.example.com`.
+    #[serde(default)]
+    pub url: Option<String>,
+    /// REST API base URL, derived from `url` when omitted.
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Organization (GitLab group) the repositories are created in, the one of `REPO_URL`
+    /// when omitted.
+    #[serde(default)]
+    pub organization: Option<String>,
+}
+
+impl ServerSettings {
+    pub fn validate(&self, provider: ProviderKind) -> Result<()> {
+        for (field, url) in [("url", &self.url), ("api_url", &self.api_url)] {
+            if let Some(url) = url {
+                if !url.starts_with("https://") && !url.starts_with("http://") {
+                    return Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("server.{} must be an http(s) URL, got '{}'", field, url),
+                    ));
+                }
+            }
+        }
+        if self.url.is_none() && provider.default_url().is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("server.url is required with the {} provider", provider),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn url(&self, provider: ProviderKind) -> &str {
+        self.url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/'))
+            .or(provider.default_url())
+            .unwrap_or_default()
+    }
+
+    pub fn api_url(&self, provider: ProviderKind) -> String {
+        if let Some(api_url) = &self.api_url {
+            return api_url.trim_end_matches('/').to_string();
+        }
+        match provider {
+            // github.com is the only instance with the API on its own host
+            ProviderKind::GitHub if self.url(provider) == DEFAULT_GITHUB_URL => DEFAULT_GITHUB_API_URL.to_string(),
+            _ => format!("{}{}", self.url(provider), provider.api_path()),
+        }
+    }
+
+    /// The instance is not the public one of the provider.
+    pub fn is_self_hosted(&self, provider: ProviderKind) -> bool {
+        provider.default_url() != Some(self.url(provider)) || self.api_url.is_some()
+    }
+}
+
+/// Issue label of the created repository, from `labels`. Labels that already exist, such as the
+/// defaults of a new GitHub repository, get the configured color and description.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct LabelDefinition {
+    pub name: String,
+    /// Hex color, with or without the leading `#`.
+    #[serde(default = "default_label_color")]
+    pub color: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+fn default_label_color() -> String {
+    DEFAULT_LABEL_COLOR.to_string()
+}
+
+impl LabelDefinition {
+    /// Six hex digits, without `#`.
+    pub fn hex_color(&self) -> &str {
+        self.color.trim_start_matches('#')
+    }
+
+    pub fn description(&self) -> &str {
+        self.description.as_deref().unwrap_or_default()
+    }
+}
+
+/// Label names are unique ignoring case, as GitHub compares them, and colors are six hex digits.
+pub fn validate_labels(labels: &[LabelDefinition]) -> Result<()> {
+    for (index, label) in labels.iter().enumerate() {
+        let color = label.hex_color();
+        let problem = if label.name.trim().is_empty() {
+            Some("the name is empty".to_string())
+        } else if label.name.chars().count() > MAX_LABEL_NAME_LENGTH {
+            Some(format!("the name is longer than {} characters", MAX_LABEL_NAME_LENGTH))
+        } else if color.len() != 6 || !color.chars().all(|c| c.is_ascii_hexdigit()) {
+            Some(format!("'{}' is not a color, use six hex digits such as \"d73a4a\"", label.color))
+        } else if label.description().chars().count() > MAX_LABEL_DESCRIPTION_LENGTH {
+            Some(format!("the description is longer than {} characters", MAX_LABEL_DESCRIPTION_LENGTH))
+        } else if labels[..index].iter().any(|other| other.name.eq_ignore_ascii_case(&label.name)) {
+            Some("it is listed twice".to_string())
+        } else {
+            None
+        };
+        if let Some(problem) = problem {
+            return Err(Error::new(ErrorKind::InvalidData, format!("Invalid label '{}': {}", label.name, problem)));
+        }
+    }
+    Ok(())
+}
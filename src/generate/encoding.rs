@@ -0,0 +1,123 @@
+use std::io::{self, Error, ErrorKind};
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+use crate::utils::context;
+
+/// Number of leading bytes inspected to decide whether a file is binary.
+const BINARY_SNIFF_LEN: usize = 8000;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    /// Both kinds are used, the content is written back as it is.
+    Mixed,
+}
+
+/// A decoded text file, remembering what is needed to write it back byte-compatible.
+pub struct TextFile {
+    pub content: String,
+    encoding: &'static Encoding,
+    has_bom: bool,
+    line_ending: LineEnding,
+}
+
+impl TextFile {
+    /// Decodes a file. Returns `None` for binary files, which must not be rendered.
+    ///
+    /// Without an explicit encoding, the encoding is taken from the BOM and defaults to UTF-8.
+    pub fn decode(bytes: &[u8], explicit_encoding: Option<&str>) -> io::Result<Option<Self>> {
+        let (bom_encoding, bom_len) = match Encoding::for_bom(bytes) {
+            Some((encoding, len)) => (Some(encoding), len),
+            None => (None, 0),
+        };
+
+        let encoding = match explicit_encoding {
+            Some(label) => Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| {
+                Error::new(ErrorKind::InvalidInput, format!("Unknown encoding '{}'", label))
+            })?,
+            None => match bom_encoding {
+                Some(encoding) => encoding,
+                None if is_binary(bytes) => return Ok(None),
+                None => UTF_8,
+            },
+        };
+        context::debug_print(&format!("Decoding file as {}", encoding.name()));
+
+        // A BOM only counts when it matches the encoding in use
+        let has_bom = bom_encoding == Some(encoding);
+        let body = if has_bom { &bytes[bom_len..] } else { bytes };
+
+        let (content, had_errors) = encoding.decode_without_bom_handling(body);
+        if had_errors {
+            if explicit_encoding.is_some() {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("File is not valid {}", encoding.name()),
+                ));
+            }
+            // Not UTF-8 and no encoding declared: treat it as binary rather than corrupting it
+            return Ok(None);
+        }
+
+        let crlf_count = content.matches("\r\n").count();
+        let line_ending = match crlf_count {
+            0 => LineEnding::Lf,
+            count if count == content.matches('\n').count() => LineEnding::Crlf,
+            _ => LineEnding::Mixed,
+        };
+
+        Ok(Some(Self {
+            content: content.into_owned(),
+            encoding,
+            has_bom,
+            line_ending,
+        }))
+    }
+
+    /// Encodes new content with the original encoding, BOM and line endings.
+    pub fn encode(&self, content: &str) -> io::Result<Vec<u8>> {
+        let content = match self.line_ending {
+            LineEnding::Lf => content.replace("\r\n", "\n"),
+            LineEnding::Crlf => content.replace("\r\n", "\n").replace('\n', "\r\n"),
+            LineEnding::Mixed => content.to_string(),
+        };
+
+        let mut bytes = Vec::with_capacity(content.len() + 3);
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            // encoding_rs only decodes UTF-16, encoding is done by hand
+            let little_endian = self.encoding == UTF_16LE;
+            if self.has_bom {
+                bytes.extend_from_slice(if little_endian { UTF16LE_BOM } else { UTF16BE_BOM });
+            }
+            for unit in content.encode_utf16() {
+                let unit_bytes = if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() };
+                bytes.extend_from_slice(&unit_bytes);
+            }
+            return Ok(bytes);
+        }
+
+        if self.has_bom && self.encoding == UTF_8 {
+            bytes.extend_from_slice(UTF8_BOM);
+        }
+        let (encoded, _, had_errors) = self.encoding.encode(&content);
+        if had_errors {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Replacement values cannot be represented in {}", self.encoding.name()),
+            ));
+        }
+        bytes.extend_from_slice(&encoded);
+        Ok(bytes)
+    }
+}
+
+/// A NUL byte in the first bytes is a reliable sign of binary content (images, fonts, archives).
+fn is_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0)
+}
@@ -1,14 +1,23 @@
-use crate::config::{Replacement, EXCLUDED_DIRS, EXCLUDED_FILES};
+use crate::config::{Replacement, EXCLUDED_FILES};
 
 use indexmap::IndexMap;
 use serde_json::{self, Value};
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
 use std::{fs, io};
 
+use super::encoding::TextFile;
 use super::functions;
+use super::ignore_rules::IgnoreRules;
+use super::json_edit;
 use crate::utils::context;
 
+/// Copies a template directory, keeping file permissions and symlinks and skipping ignored files.
 pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let ignore_rules = IgnoreRules::load(src)?;
+    copy_dir_recursive(src, src, dst, &ignore_rules)
+}
+
+fn copy_dir_recursive(root: &Path, src: &Path, dst: &Path, ignore_rules: &IgnoreRules) -> std::io::Result<()> {
     context::debug_print(&format!("Copying directory from '{}' to '{}'", src.display(), dst.display()));
     fs::create_dir_all(dst)?;
     for entry in fs::read_dir(src)? {
@@ -16,77 +25,138 @@ pub fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
         let file_name = entry.file_name();
         let file_name_str = file_name.to_string_lossy();
 
-        if EXCLUDED_DIRS.contains(&file_name_str.as_ref()) {
-            context::debug_print(&format!("Skipping excluded directory: {}", file_name_str));
-            continue;
-        }
-
         if EXCLUDED_FILES.contains(&file_name_str.as_ref()) {
             context::debug_print(&format!("Skipping excluded file: {}", file_name_str));
             continue;
         }
 
+        // file_type does not follow symlinks, so links are never traversed
         let file_type = entry.file_type()?;
-        if file_type.is_dir() {
+
+        if ignore_rules.is_ignored(&entry.path(), file_type.is_dir()) {
+            context::debug_print(&format!("Skipping ignored entry: {}", file_name_str));
+            continue;
+        }
+        if file_type.is_symlink() {
+            context::debug_print(&format!("Recreating symlink: {}", file_name_str));
+            copy_symlink(root, &entry.path(), &dst.join(entry.file_name()))?;
+        } else if file_type.is_dir() {
             context::debug_print(&format!("Copying subdirectory: {}", file_name_str));
-            copy_dir_all(&entry.path(), &dst.join(entry.file_name()))?;
+            copy_dir_recursive(root, &entry.path(), &dst.join(entry.file_name()), ignore_rules)?;
         } else {
+            // fs::copy also copies the permission bits, keeping scripts executable
             context::debug_print(&format!("Copying file: {}", file_name_str));
             fs::copy(entry.path(), dst.join(entry.file_name()))?;
         }
     }
+
+    // Applied last so a read-only directory can still be filled
+    fs::set_permissions(dst, fs::metadata(src)?.permissions())?;
+    Ok(())
+}
+
+/// Recreates a symlink with the same relative target, refusing links that leave the template.
+fn copy_symlink(root: &Path, link: &Path, dst: &Path) -> std::io::Result<()> {
+    let target = fs::read_link(link)?;
+    let resolved = normalize_path(&link.parent().unwrap_or(root).join(&target));
+
+    if target.is_absolute() || !resolved.starts_with(normalize_path(root)) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Symlink '{}' points to '{}', outside of the template",
+                link.strip_prefix(root).unwrap_or(link).display(),
+                target.display()
+            ),
+        ));
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(&target, dst)?;
+    #[cfg(windows)]
+    {
+        if resolved.is_dir() {
+            std::os::windows::fs::symlink_dir(&target, dst)?;
+        } else {
+            std::os::windows::fs::symlink_file(&target, dst)?;
+        }
+    }
     Ok(())
 }
 
-pub fn replace_in_file(file_path: &Path, replacements: &[Replacement]) -> io::Result<()> {
+/// Resolves `.` and `..` components without touching the filesystem.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Applies the replacements to one file and returns the names of the text replacements
+/// that found nothing to replace in it.
+pub fn replace_in_file(
+    file_path: &Path,
+    replacements: &[Replacement],
+    encoding: Option<&str>,
+) -> io::Result<Vec<String>> {
     context::debug_print(&format!("Processing file: {}", file_path.display()));
     context::debug_print(&format!("Found {} replacements to apply", replacements.len()));
     
-    let content = fs::read_to_string(file_path)?;
+    let bytes = fs::read(file_path)?;
+    let Some(file) = TextFile::decode(&bytes, encoding)? else {
+        println!("Skipping binary file: {}", file_path.file_name().unwrap_or_default().to_string_lossy());
+        context::report_issue("Binary file listed for replacement was skipped (set \"encoding\" if it is text)");
+        return Ok(Vec::new());
+    };
 
-    if file_path.extension().and_then(|s| s.to_str()) == Some("json") {
+    let extension = file_path.extension().and_then(|s| s.to_str());
+    // JSON replacements add the keys they do not find, they always apply
+    let (new_content, unmatched) = if matches!(extension, Some("json") | Some("jsonc") | Some("json5")) {
         context::debug_print("Detected JSON file, using JSON replacement logic");
-        replace_in_json_file(file_path, &content, replacements)
+        (replace_in_json_file(&file.content, replacements)?, Vec::new())
     } else {
         context::debug_print("Using text replacement logic");
-        replace_in_text_file(file_path, &content, replacements)
-    }
-}
+        replace_in_text_file(&file.content, replacements)
+    };
 
-fn write_json_to_file(file_path: &Path, ordered_map: IndexMap<String, Value>) -> io::Result<()> {
-    context::debug_print(&format!("Writing JSON file: {}", file_path.display()));
-    context::debug_print(&format!("JSON contains {} keys", ordered_map.len()));
-    
-    let json_str = serde_json::to_string_pretty(&ordered_map)?;
-    fs::write(file_path, json_str)
+    context::debug_print(&format!("Writing file: {}", file_path.display()));
+    fs::write(file_path, file.encode(&new_content)?)?;
+    Ok(unmatched)
 }
 
 fn replace_in_json_file(
-    file_path: &Path,
     content: &str,
     replacements: &[Replacement],
-) -> io::Result<()> {
+) -> io::Result<String> {
     context::debug_print("Parsing JSON content");
-    let template_json: IndexMap<String, Value> = serde_json::from_str(content)?;
+    let template_json: IndexMap<String, Value> = json_edit::parse(content)?;
     context::debug_print(&format!("Template JSON contains {} keys", template_json.len()));
     
     let mut ordered_map = functions::create_ordered_map(&template_json, replacements);
     functions::update_existing_values(&mut ordered_map, replacements);
-    write_json_to_file(file_path, ordered_map)
+    context::debug_print(&format!("JSON contains {} keys", ordered_map.len()));
+    json_edit::apply_changes(content, &template_json, &ordered_map)
 }
 
 fn replace_in_text_file(
-    file_path: &Path,
     content: &str,
     replacements: &[Replacement],
-) -> io::Result<()> {
+) -> (String, Vec<String>) {
     context::debug_print("Applying text replacements");
     let mut new_content = content.to_string();
+    let mut unmatched = Vec::new();
 
     for replacement in replacements {
         if let Some(value) = crate::utils::context::get_variable(&replacement.name) {
             let json_value = functions::convert_value_to_json(&value, &replacement.type_);
-            let formatted_value = serde_json::to_string(&json_value).unwrap_or_else(|_| value);
+            let formatted_value = serde_json::to_string(&json_value).unwrap_or(value);
 
             let old_content = new_content.clone();
             new_content = new_content.replace(&format!("{{{{{}}}}}", replacement.name), &formatted_value);
@@ -95,12 +165,13 @@ fn replace_in_text_file(
             if old_content != new_content {
                 context::debug_print(&format!("Applied replacement for '{}' with value '{}'", replacement.name, formatted_value));
             } else {
-                context::debug_print(&format!("No matches found for replacement '{}'", replacement.name));
+                context::debug_print(&format!("No occurrence of '{{{{{}}}}}' or '{}' found", replacement.name, replacement.key));
+                unmatched.push(replacement.name.clone());
             }
         } else {
-            context::debug_print(&format!("Warning: Variable '{}' not found for text replacement", replacement.name));
+            context::report_issue(&format!("Variable '{}' not found for text replacement", replacement.name));
         }
     }
 
-    fs::write(file_path, new_content)
+    (new_content, unmatched)
 }
@@ -47,7 +47,7 @@ pub fn create_ordered_map(
                     ordered_map.insert(replacement.key.clone(), json_value);
                     context::debug_print(&format!("Added new key '{}' with value from variable '{}'", replacement.key, replacement.name));
                 } else {
-                    context::debug_print(&format!("Warning: Variable '{}' not found for key '{}'", replacement.name, replacement.key));
+                    context::report_issue(&format!("Variable '{}' not found for key '{}'", replacement.name, replacement.key));
                 }
             } else {
                 context::debug_print(&format!("Key '{}' already exists in template, skipping", replacement.key));
@@ -63,7 +63,7 @@ pub fn create_ordered_map(
                     ordered_map.insert(replacement.key.clone(), json_value);
                     context::debug_print(&format!("Added new key '{}' with value from variable '{}'", replacement.key, replacement.name));
                 } else {
-                    context::debug_print(&format!("Warning: Variable '{}' not found for key '{}'", replacement.name, replacement.key));
+                    context::report_issue(&format!("Variable '{}' not found for key '{}'", replacement.name, replacement.key));
                 }
             }
         }
@@ -85,10 +85,10 @@ pub fn update_existing_values(
                 context::debug_print(&format!("Updated key '{}' from '{}' to '{}'", replacement.key, existing_value, json_value));
                 *existing_value = json_value;
             } else {
-                context::debug_print(&format!("Warning: Key '{}' not found in template for replacement", replacement.key));
+                context::report_issue(&format!("Key '{}' not found in template for replacement", replacement.key));
             }
         } else {
-            context::debug_print(&format!("Warning: Variable '{}' not found for replacement of key '{}'", replacement.name, replacement.key));
+            context::report_issue(&format!("Variable '{}' not found for replacement of key '{}'", replacement.name, replacement.key));
         }
     }
 }
@@ -0,0 +1,130 @@
+use std::env;
+use std::io::{self, Error};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::config::{
+    Hook, TemplateJson, DEFAULT_HOOK_TIMEOUT_SECS, HOOK_BASE_ENVIRONMENT, HOOK_STRIPPED_ENVIRONMENT, HOOK_VARIABLE_PREFIX,
+};
+use crate::generate::manifest::is_secret_variable;
+use crate::utils::{command, context};
+
+/// Runs the hooks of one stage in order, in `project_dir`, given to hooks as `PG_PROJECT_DIR`.
+///
+/// `final_project_dir` is where the project ends up, given as `PG_FINAL_PROJECT_DIR`. It differs
+/// from `project_dir` for pre-generate hooks, which run in the staging directory. `config`
+/// tells which variables are secret.
+/// A failing hook stops generation unless it sets `continue_on_error`.
+pub fn run_hooks(
+    stage: &str,
+    hooks: &[Hook],
+    project_dir: &Path,
+    final_project_dir: &Path,
+    template_path: &Path,
+    config: &TemplateJson,
+) -> io::Result<()> {
+    if hooks.is_empty() {
+        return Ok(());
+    }
+    context::debug_print(&format!("Running {} {} hook(s)", hooks.len(), stage));
+
+    for hook in hooks {
+        println!("Running {} hook '{}'", stage, hook.label());
+        let timeout = Duration::from_secs(hook.timeout_secs.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+
+        let mut hook_command = command::shell_command(&hook.command);
+        restrict_environment(&mut hook_command);
+        hook_command
+            .current_dir(project_dir)
+            .envs(hook_environment(project_dir, final_project_dir, template_path, config));
+
+        let output = command::run_captured(&mut hook_command, timeout)
+            .map_err(|e| Error::new(e.kind(), format!("Failed to run {} hook '{}': {}", stage, hook.label(), e)))?;
+
+        if output.success() {
+            if context::is_debug_mode() {
+                print_output(&output.lines);
+            }
+            context::debug_print(&format!("Hook '{}' completed successfully", hook.label()));
+            continue;
+        }
+
+        print_output(&output.lines);
+        let reason = match output.status {
+            Some(status) => format!("exited with {}", status),
+            None => format!("timed out after {}s", timeout.as_secs()),
+        };
+
+        if hook.continue_on_error {
+            println!("Warning: {} hook '{}' {}, continuing", stage, hook.label(), reason);
+            continue;
+        }
+        return Err(Error::other(format!("{} hook '{}' {}", stage, hook.label(), reason)));
+    }
+    Ok(())
+}
+
+/// Removes credentials from the inherited environment, or starts from an empty one when
+/// the configuration asks for it.
+fn restrict_environment(hook_command: &mut Command) {
+    let settings = context::get_options().hook_environment;
+    let allowed = |name: &str| settings.allow.iter().any(|allowed| allowed == name);
+
+    if settings.clear {
+        context::debug_print("Running hook with a cleared environment");
+        hook_command.env_clear();
+        let kept = HOOK_BASE_ENVIRONMENT.iter().copied().chain(settings.allow.iter().map(String::as_str));
+        for name in kept {
+            if let Some(value) = env::var_os(name) {
+                hook_command.env(name, value);
+            }
+        }
+        return;
+    }
+
+    for name in HOOK_STRIPPED_ENVIRONMENT.iter().filter(|name| !allowed(name)) {
+        hook_command.env_remove(name);
+    }
+}
+
+/// Template variables as `PG_VAR_<NAME>`, plus the project and template locations. Secret
+/// variables are left out, like in the manifest.
+fn hook_environment(
+    project_dir: &Path,
+    final_project_dir: &Path,
+    template_path: &Path,
+    config: &TemplateJson,
+) -> Vec<(String, String)> {
+    let mut environment: Vec<(String, String)> = context::get_variable_names()
+        .into_iter()
+        .filter(|name| !is_secret_variable(name, config))
+        .filter_map(|name| {
+            let value = context::get_variable(&name)?;
+            Some((format!("{}{}", HOOK_VARIABLE_PREFIX, environment_name(&name)), value))
+        })
+        .collect();
+
+    // Absolute, hooks do not run from the directory the project path is relative to
+    let absolute = |path: &Path| std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    environment.push(("PG_PROJECT_DIR".to_string(), absolute(project_dir).display().to_string()));
+    environment.push(("PG_FINAL_PROJECT_DIR".to_string(), absolute(final_project_dir).display().to_string()));
+    environment.push(("PG_TEMPLATE_DIR".to_string(), template_path.display().to_string()));
+    if let Some(project_name) = context::get_variable("project_name") {
+        environment.push(("PG_PROJECT_NAME".to_string(), project_name));
+    }
+    environment
+}
+
+/// Upper-cases a variable name and replaces characters not allowed in environment names.
+fn environment_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+fn print_output(lines: &[String]) {
+    for line in lines {
+        println!("  | {}", line);
+    }
+}
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use ignore::gitignore::Gitignore;
+use ignore::Match;
+
+use crate::config::{EXCLUDED_DIRS, TEMPLATE_IGNORE_FILE};
+use crate::utils::context;
+
+/// Decides which template files are not copied into the generated project.
+///
+/// Rules are read, by decreasing priority, from the template's `.templateignore`,
+/// the template's `.gitignore` and the `.gitignore` at the root of the template
+/// repository. `EXCLUDED_DIRS` is only used when the template has no `.templateignore`.
+pub struct IgnoreRules {
+    matchers: Vec<Gitignore>,
+    use_fallback: bool,
+}
+
+impl IgnoreRules {
+    pub fn load(template_path: &Path) -> std::io::Result<Self> {
+        let mut matchers = Vec::new();
+
+        let template_ignore = template_path.join(TEMPLATE_IGNORE_FILE);
+        let use_fallback = !template_ignore.is_file();
+        if !use_fallback {
+            matchers.push(load_file(&template_ignore)?);
+        }
+
+        let gitignore = template_path.join(".gitignore");
+        if gitignore.is_file() {
+            matchers.push(load_file(&gitignore)?);
+        }
+
+        // Templates live in a subdirectory of the template repository
+        let repo_root = template_path
+            .ancestors()
+            .skip(1)
+            .find(|dir| dir.join(".git").exists());
+        if let Some(repo_gitignore) = repo_root.map(|root| root.join(".gitignore")) {
+            if repo_gitignore.is_file() {
+                matchers.push(load_file(&repo_gitignore)?);
+            }
+        }
+
+        context::debug_print(&format!(
+            "Loaded {} ignore file(s), fallback exclusions {}",
+            matchers.len(),
+            if use_fallback { "enabled" } else { "disabled" }
+        ));
+        Ok(Self { matchers, use_fallback })
+    }
+
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for matcher in &self.matchers {
+            match matcher.matched(path, is_dir) {
+                Match::Ignore(glob) => {
+                    context::debug_print(&format!("'{}' ignored by '{}'", path.display(), glob.original()));
+                    return true;
+                }
+                Match::Whitelist(glob) => {
+                    context::debug_print(&format!("'{}' kept by '{}'", path.display(), glob.original()));
+                    return false;
+                }
+                Match::None => {}
+            }
+        }
+
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        self.use_fallback && is_dir && EXCLUDED_DIRS.contains(&file_name.as_ref())
+    }
+}
+
+fn load_file(path: &Path) -> std::io::Result<Gitignore> {
+    context::debug_print(&format!("Reading ignore rules from: {}", path.display()));
+    let (matcher, error) = Gitignore::new(path);
+    if let Some(e) = error {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Invalid ignore file '{}': {}", path.display(), e),
+        ));
+    }
+    Ok(matcher)
+}
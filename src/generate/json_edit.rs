@@ -0,0 +1,482 @@
+use std::io::{self, Error, ErrorKind};
+
+use indexmap::IndexMap;
+use serde::Serialize;
+use serde_json::ser::PrettyFormatter;
+use serde_json::Value;
+
+use crate::utils::context;
+
+/// A member of the root object, located in the original text.
+struct Member {
+    key: String,
+    key_start: usize,
+    value_start: usize,
+    value_end: usize,
+}
+
+/// Parses JSON that may contain comments and trailing commas (JSONC, as used by
+/// tsconfig.json or VS Code settings), and the unquoted keys and single-quoted strings
+/// of JSON5. Other JSON5 extensions (hexadecimal numbers, `Infinity`, ...) are rejected.
+pub fn parse(content: &str) -> io::Result<IndexMap<String, Value>> {
+    Ok(serde_json::from_str(&to_json(content))?)
+}
+
+/// Rewrites `original` so its root object matches `updated`, touching only the members
+/// whose value changed or that were added. Indentation, comments, key order and the
+/// final newline of the original are kept.
+pub fn apply_changes(
+    original: &str,
+    template_json: &IndexMap<String, Value>,
+    updated: &IndexMap<String, Value>,
+) -> io::Result<String> {
+    let indent = detect_indent(original);
+    let members = scan_root_members(original)?;
+
+    if members.is_empty() {
+        context::debug_print("Empty JSON object, serializing it entirely");
+        return serialize_document(original, updated, &indent);
+    }
+
+    // Edits are collected as (position, replaced length, text) and applied from the end
+    let mut edits: Vec<(usize, usize, String)> = Vec::new();
+    let mut insert_after = None;
+    let mut pending: Vec<String> = Vec::new();
+
+    for (key, value) in updated {
+        match members.iter().find(|m| &m.key == key) {
+            Some(member) => {
+                flush_insertions(&mut edits, insert_after, &mut pending, members[0].key_start, &indent);
+                if template_json.get(key) != Some(value) {
+                    context::debug_print(&format!("Rewriting value of key '{}'", key));
+                    let text = serialize_value(value, &indent)?;
+                    edits.push((member.value_start, member.value_end - member.value_start, text));
+                }
+                insert_after = Some(member.value_end);
+            }
+            None => {
+                context::debug_print(&format!("Inserting key '{}'", key));
+                pending.push(format!("\"{}\": {}", escape_key(key)?, serialize_value(value, &indent)?));
+            }
+        }
+    }
+    flush_insertions(&mut edits, insert_after, &mut pending, members[0].key_start, &indent);
+
+    let mut result = original.to_string();
+    edits.sort_by_key(|(position, _, _)| *position);
+    for (position, length, text) in edits.into_iter().rev() {
+        result.replace_range(position..position + length, &text);
+    }
+    Ok(result)
+}
+
+/// Queues the pending new members after the member ending at `after`, or before the first
+/// member when no member precedes them.
+fn flush_insertions(
+    edits: &mut Vec<(usize, usize, String)>,
+    after: Option<usize>,
+    pending: &mut Vec<String>,
+    first_key_start: usize,
+    indent: &str,
+) {
+    if pending.is_empty() {
+        return;
+    }
+
+    match after {
+        Some(position) => {
+            let text: String = pending
+                .iter()
+                .map(|member| format!(",\n{}{}", indent, member))
+                .collect();
+            edits.push((position, 0, text));
+        }
+        None => {
+            let text: String = pending
+                .iter()
+                .map(|member| format!("{},\n{}", member, indent))
+                .collect();
+            edits.push((first_key_start, 0, text));
+        }
+    }
+    pending.clear();
+}
+
+fn serialize_value(value: &Value, indent: &str) -> io::Result<String> {
+    let mut buffer = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, PrettyFormatter::with_indent(indent.as_bytes()));
+    value.serialize(&mut serializer)?;
+    let text = String::from_utf8(buffer).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+    // Nested lines are shifted by one level, the depth of root members
+    Ok(text.replace('\n', &format!("\n{}", indent)))
+}
+
+fn serialize_document(original: &str, map: &IndexMap<String, Value>, indent: &str) -> io::Result<String> {
+    let mut buffer = Vec::new();
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, PrettyFormatter::with_indent(indent.as_bytes()));
+    map.serialize(&mut serializer)?;
+    let mut text = String::from_utf8(buffer).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+    if original.ends_with('\n') {
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+fn escape_key(key: &str) -> io::Result<String> {
+    let quoted = serde_json::to_string(key)?;
+    Ok(quoted[1..quoted.len() - 1].to_string())
+}
+
+/// Returns the indentation unit of the document (a tab or a number of spaces).
+fn detect_indent(content: &str) -> String {
+    content
+        .lines()
+        .skip(1)
+        .map(|line| {
+            let trimmed = line.trim_start_matches([' ', '\t']);
+            (&line[..line.len() - trimmed.len()], trimmed)
+        })
+        .find(|(whitespace, rest)| !whitespace.is_empty() && !rest.is_empty())
+        .map(|(whitespace, _)| whitespace.to_string())
+        .unwrap_or_else(|| "  ".to_string())
+}
+
+/// Character-level scanner aware of strings and comments.
+struct Scanner<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(content: &'a str) -> Self {
+        Self { bytes: content.as_bytes(), position: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::new(ErrorKind::InvalidData, format!("{} at byte {}", message, self.position))
+    }
+
+    /// Skips whitespace and comments.
+    fn skip_trivia(&mut self) {
+        while let Some(byte) = self.peek() {
+            match byte {
+                b' ' | b'\t' | b'\n' | b'\r' => self.position += 1,
+                b'/' if self.bytes.get(self.position + 1) == Some(&b'/') => {
+                    while self.peek().is_some_and(|b| b != b'\n') {
+                        self.position += 1;
+                    }
+                }
+                b'/' if self.bytes.get(self.position + 1) == Some(&b'*') => {
+                    self.position += 2;
+                    while self.position < self.bytes.len()
+                        && !(self.bytes[self.position] == b'*' && self.bytes.get(self.position + 1) == Some(&b'/'))
+                    {
+                        self.position += 1;
+                    }
+                    self.position = (self.position + 2).min(self.bytes.len());
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: u8) -> io::Result<()> {
+        self.skip_trivia();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("Expected '{}'", expected as char)));
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    /// Skips a string delimited by the current quote, double or single.
+    fn skip_string(&mut self) -> io::Result<()> {
+        let quote = self.bytes[self.position];
+        self.position += 1;
+        while let Some(byte) = self.peek() {
+            self.position += 1;
+            match byte {
+                b'\\' => self.position += 1,
+                byte if byte == quote => return Ok(()),
+                _ => {}
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+
+    /// Skips an unquoted JSON5 key or a literal such as `true`.
+    fn skip_identifier(&mut self) {
+        while self.peek().is_some_and(is_identifier_byte) {
+            self.position += 1;
+        }
+    }
+
+    /// Skips one value and returns its span.
+    fn skip_value(&mut self) -> io::Result<(usize, usize)> {
+        self.skip_trivia();
+        let start = self.position;
+        match self.peek() {
+            Some(b'"') | Some(b'\'') => self.skip_string()?,
+            Some(b'{') | Some(b'[') => {
+                let mut depth = 0usize;
+                loop {
+                    self.skip_trivia();
+                    match self.peek() {
+                        Some(b'"') | Some(b'\'') => {
+                            self.skip_string()?;
+                            continue;
+                        }
+                        Some(b'{') | Some(b'[') => depth += 1,
+                        Some(b'}') | Some(b']') => depth -= 1,
+                        None => return Err(self.error("Unterminated object or array")),
+                        _ => {}
+                    }
+                    self.position += 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+            }
+            Some(_) => {
+                while self.peek().is_some_and(|b| !matches!(b, b',' | b'}' | b']' | b' ' | b'\t' | b'\n' | b'\r' | b'/')) {
+                    self.position += 1;
+                }
+            }
+            None => return Err(self.error("Expected a value")),
+        }
+        Ok((start, self.position))
+    }
+}
+
+fn scan_root_members(content: &str) -> io::Result<Vec<Member>> {
+    let mut scanner = Scanner::new(content);
+    let mut members = Vec::new();
+    scanner.expect(b'{')?;
+
+    loop {
+        scanner.skip_trivia();
+        match scanner.peek() {
+            Some(b'}') => break,
+            Some(b',') => {
+                scanner.position += 1;
+                continue;
+            }
+            Some(b'"') | Some(b'\'') => {
+                let key_start = scanner.position;
+                scanner.skip_string()?;
+                let key: String = serde_json::from_str(&to_json_string(&content[key_start..scanner.position]))?;
+                scanner.expect(b':')?;
+                let (value_start, value_end) = scanner.skip_value()?;
+                members.push(Member { key, key_start, value_start, value_end });
+            }
+            Some(byte) if is_identifier_byte(byte) => {
+                let key_start = scanner.position;
+                scanner.skip_identifier();
+                let key = content[key_start..scanner.position].to_string();
+                scanner.expect(b':')?;
+                let (value_start, value_end) = scanner.skip_value()?;
+                members.push(Member { key, key_start, value_start, value_end });
+            }
+            _ => return Err(scanner.error("Expected a key")),
+        }
+    }
+    Ok(members)
+}
+
+/// Removes comments and trailing commas, quotes unquoted keys and turns single-quoted
+/// strings into double-quoted ones, so serde_json can parse the document.
+fn to_json(content: &str) -> String {
+    let bytes = content.as_bytes();
+    let mut output = String::with_capacity(content.len());
+    let mut scanner = Scanner::new(content);
+    let mut pending_comma = false;
+
+    while scanner.position < bytes.len() {
+        let before = scanner.position;
+        scanner.skip_trivia();
+        if scanner.position > before {
+            // Keep line breaks so parse errors point at the right line
+            output.extend(content[before..scanner.position].chars().filter(|c| *c == '\n'));
+            continue;
+        }
+
+        let byte = bytes[scanner.position];
+        if pending_comma && byte != b'}' && byte != b']' && byte != b',' {
+            output.push(',');
+        }
+        if byte != b',' {
+            pending_comma = false;
+        }
+
+        match byte {
+            b'"' | b'\'' => {
+                let start = scanner.position;
+                if scanner.skip_string().is_err() {
+                    scanner.position = bytes.len();
+                }
+                output.push_str(&to_json_string(&content[start..scanner.position]));
+            }
+            b',' => {
+                if pending_comma {
+                    output.push(',');
+                }
+                pending_comma = true;
+                scanner.position += 1;
+            }
+            byte if is_identifier_byte(byte) && !byte.is_ascii_digit() => {
+                let start = scanner.position;
+                scanner.skip_identifier();
+                let identifier = &content[start..scanner.position];
+                let after = scanner.position;
+                scanner.skip_trivia();
+                let is_key = scanner.peek() == Some(b':');
+                scanner.position = after;
+                if is_key {
+                    output.push('"');
+                    output.push_str(identifier);
+                    output.push('"');
+                } else {
+                    output.push_str(identifier);
+                }
+            }
+            _ => {
+                let start = scanner.position;
+                scanner.position += content[start..].chars().next().map(char::len_utf8).unwrap_or(1);
+                output.push_str(&content[start..scanner.position]);
+            }
+        }
+    }
+    if pending_comma {
+        output.push(',');
+    }
+    output
+}
+
+/// Rewrites a single-quoted JSON5 string as a JSON string, double-quoted strings are returned
+/// as they are. Escaped line breaks, which continue a JSON5 string, are removed.
+fn to_json_string(string: &str) -> String {
+    if !string.starts_with('\'') {
+        return string.to_string();
+    }
+    let inner = string.strip_prefix('\'').unwrap_or(string);
+    let inner = inner.strip_suffix('\'').unwrap_or(inner);
+
+    let mut output = String::with_capacity(string.len());
+    output.push('"');
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\'') => output.push('\''),
+                Some('\n') => {}
+                Some(escaped) => {
+                    output.push('\\');
+                    output.push(escaped);
+                }
+                None => output.push('\\'),
+            },
+            '"' => output.push_str("\\\""),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+    output
+}
+
+/// Bytes of JSON5 identifiers (unquoted keys) and of literals such as `true` or numbers.
+/// Non-ASCII bytes are accepted so Unicode identifiers stay whole.
+fn is_identifier_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$' || byte >= 0x80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn object(value: Value) -> IndexMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    /// Parses `original`, applies `changes` on top of it and rewrites the document.
+    fn edit(original: &str, changes: Value) -> String {
+        let template = parse(original).unwrap();
+        let mut updated = template.clone();
+        updated.extend(object(changes));
+        apply_changes(original, &template, &updated).unwrap()
+    }
+
+    #[test]
+    fn parse_accepts_comments_and_trailing_commas() {
+        let parsed = parse("{\n  // line\n  \"a\": 1, /* block */\n  \"b\": [1, 2,],\n}\n").unwrap();
+        assert_eq!(parsed, object(json!({ "a": 1, "b": [1, 2] })));
+    }
+
+    #[test]
+    fn parse_keeps_comment_markers_inside_strings() {
+        let parsed = parse(r#"{ "url": "https://example.com/*x*/", "glob": "src/**" }"#).unwrap();
+        assert_eq!(parsed, object(json!({ "url": "https://example.com/*x*/", "glob": "src/**" })));
+    }
+
+    #[test]
+    fn parse_accepts_json5_keys_and_strings() {
+        let parsed = parse("{\n  name: 'it\\'s \"quoted\"',\n  $id: true,\n  'quoted key': null,\n}").unwrap();
+        assert_eq!(parsed, object(json!({ "name": "it's \"quoted\"", "$id": true, "quoted key": null })));
+    }
+
+    #[test]
+    fn parse_rejects_unsupported_json5() {
+        assert!(parse("{ a: 0x1F }").is_err());
+    }
+
+    #[test]
+    fn apply_changes_rewrites_only_changed_values() {
+        let original = "{\n\t\"name\": \"template\", // kept\n\t\"version\": \"1.0.0\"\n}\n";
+        let edited = edit(original, json!({ "name": "demo" }));
+        assert_eq!(edited, "{\n\t\"name\": \"demo\", // kept\n\t\"version\": \"1.0.0\"\n}\n");
+    }
+
+    #[test]
+    fn apply_changes_inserts_new_keys_after_the_previous_member() {
+        let original = "{\n  \"a\": 1,\n  \"b\": 2\n}\n";
+        let template = parse(original).unwrap();
+        let updated = object(json!({ "a": 1, "new": { "x": true }, "b": 2 }));
+        let edited = apply_changes(original, &template, &updated).unwrap();
+        assert_eq!(edited, "{\n  \"a\": 1,\n  \"new\": {\n    \"x\": true\n  },\n  \"b\": 2\n}\n");
+    }
+
+    #[test]
+    fn apply_changes_inserts_before_the_first_member() {
+        let original = "{\n  \"b\": 2\n}";
+        let template = parse(original).unwrap();
+        let updated = object(json!({ "a": 1, "b": 2 }));
+        assert_eq!(apply_changes(original, &template, &updated).unwrap(), "{\n  \"a\": 1,\n  \"b\": 2\n}");
+    }
+
+    #[test]
+    fn apply_changes_keeps_trailing_commas() {
+        let original = "{\n  \"a\": 1,\n  \"b\": [1, 2,],\n}\n";
+        assert_eq!(edit(original, json!({ "a": 2 })), "{\n  \"a\": 2,\n  \"b\": [1, 2,],\n}\n");
+    }
+
+    #[test]
+    fn apply_changes_serializes_an_empty_object() {
+        let edited = edit("{}\n", json!({ "name": "demo" }));
+        assert_eq!(edited, "{\n  \"name\": \"demo\"\n}\n");
+    }
+
+    #[test]
+    fn apply_changes_edits_json5() {
+        let original = "{\n  // package\n  name: 'template',\n  'version': '1.0.0',\n}\n";
+        let edited = edit(original, json!({ "name": "my-project", "private": true }));
+        assert_eq!(
+            edited,
+            "{\n  // package\n  name: \"my-project\",\n  'version': '1.0.0',\n  \"private\": true,\n}\n"
+        );
+    }
+}
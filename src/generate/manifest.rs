@@ -0,0 +1,127 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+use crate::config::{TemplateJson, MANIFEST_FILE, SECRET_VARIABLE_MARKERS};
+use crate::template::TemplateSource;
+use crate::utils::context;
+
+/// Record of how a project was generated, written to `MANIFEST_FILE` in the project.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Manifest {
+    pub generator_version: String,
+    /// Seconds since the Unix epoch.
+    pub generated_at: u64,
+    pub template: Option<TemplateSource>,
+    pub variables: BTreeMap<String, String>,
+    pub features: Vec<String>,
+    /// SHA-256 of each generated file (or the target of a symlink), keyed by its path relative to the project root.
+    pub files: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn build(project_path: &Path, config: &TemplateJson, features: Vec<String>) -> io::Result<Self> {
+        let mut variables = BTreeMap::new();
+        for name in context::get_variable_names() {
+            if is_secret_variable(&name, config) {
+                context::debug_print(&format!("Excluding secret variable '{}' from manifest", name));
+                continue;
+            }
+            if let Some(value) = context::get_variable(&name) {
+                variables.insert(name, value);
+            }
+        }
+
+        let mut files = BTreeMap::new();
+        hash_dir(project_path, project_path, &mut files)?;
+
+        Ok(Self {
+            generator_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            template: context::get_template_source(),
+            variables,
+            features,
+            files,
+        })
+    }
+
+    /// Forgets the files that were not written, a kept directory removes everything below it.
+    pub fn exclude(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            let relative = manifest_key(path);
+            let prefix = format!("{}/", relative);
+            self.files.retain(|file, _| *file != relative && !file.starts_with(&prefix));
+        }
+    }
+
+    pub fn read(project_path: &Path) -> io::Result<Self> {
+        let manifest_path = project_path.join(MANIFEST_FILE);
+        context::debug_print(&format!("Reading generation manifest: {}", manifest_path.display()));
+
+        let content = fs::read_to_string(&manifest_path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Failed to read generation manifest '{}': {}", manifest_path.display(), e),
+            )
+        })?;
+        serde_json::from_str(&content).map_err(|e| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("Invalid generation manifest: {}", e))
+        })
+    }
+
+    pub fn write(&self, project_path: &Path) -> io::Result<()> {
+        let manifest_path = project_path.join(MANIFEST_FILE);
+        context::debug_print(&format!("Writing generation manifest: {}", manifest_path.display()));
+
+        let mut content = serde_json::to_string_pretty(self)?;
+        content.push('\n');
+        fs::write(manifest_path, content)
+    }
+}
+
+/// Secret variables are marked `secret` by one of their replacements, or have a name containing
+/// one of `SECRET_VARIABLE_MARKERS`. Their values never leave the generation.
+pub fn is_secret_variable(name: &str, config: &TemplateJson) -> bool {
+    let lower = name.to_lowercase();
+    config.iter().flat_map(|c| c.replacements.iter()).any(|r| r.secret && r.name == name)
+        || SECRET_VARIABLE_MARKERS.iter().any(|marker| lower.contains(marker))
+}
+
+/// Path relative to the project root with `/` separators, as keyed in `files`.
+fn manifest_key(relative: &Path) -> String {
+    relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn hash_dir(root: &Path, dir: &Path, files: &mut BTreeMap<String, String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        let relative = manifest_key(path.strip_prefix(root).unwrap_or(&path));
+
+        if relative == ".git" || relative == MANIFEST_FILE {
+            continue;
+        }
+
+        if file_type.is_dir() {
+            hash_dir(root, &path, files)?;
+        } else if file_type.is_symlink() {
+            files.insert(relative, format!("symlink:{}", fs::read_link(&path)?.display()));
+        } else if file_type.is_file() {
+            let digest = Sha256::digest(fs::read(&path)?);
+            files.insert(relative, format!("sha256:{:x}", digest));
+        }
+    }
+    Ok(())
+}
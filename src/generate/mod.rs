@@ -1,9 +1,19 @@
 use std::io::{self, Error, ErrorKind, Result, Write};
 use std::path::{Path, PathBuf};
 
+pub mod encoding;
 pub mod file_operations;
 pub mod functions;
+pub mod hooks;
+pub mod ignore_rules;
+pub mod json_edit;
+pub mod manifest;
+pub mod overwrite;
+pub mod package_manager;
 pub mod project_generator;
+pub mod staging;
+pub mod strict;
+pub mod trust;
 
 pub fn handle_interactive_mode(template_path: &Path) -> Result<()> {
     match crate::cli::interact(template_path) {
@@ -11,7 +21,7 @@ pub fn handle_interactive_mode(template_path: &Path) -> Result<()> {
             println!("Project generated successfully");
             Ok(())
         }
-        Err(e) => Err(Error::new(ErrorKind::Other, e.to_string())),
+        Err(e) => Err(Error::other(e.to_string())),
     }
 }
 
@@ -27,7 +37,7 @@ pub fn handle_config_mode(template_path: &Path, project_name: &str) -> Result<()
 
     let mut input = String::new();
     io::stdin().read_line(&mut input).map_err(|e| {
-        Error::new(ErrorKind::Other, format!("Failed to read user input: {}", e))
+        Error::other(format!("Failed to read user input: {}", e))
     })?;
 
     let input = input.trim().to_lowercase();
@@ -42,7 +52,7 @@ pub fn handle_config_mode(template_path: &Path, project_name: &str) -> Result<()
         
         let mut custom_path = String::new();
         io::stdin().read_line(&mut custom_path).map_err(|e| {
-            Error::new(ErrorKind::Other, format!("Failed to read custom path: {}", e))
+            Error::other(format!("Failed to read custom path: {}", e))
         })?;
         
         let custom_path = custom_path.trim();
@@ -68,13 +78,8 @@ pub fn handle_config_mode_with_path(
         template_path.display()
     );
 
-    project_generator::generate_project(&template_path, &project_path)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("An error occurred while generating the project: {}", e)))?;
-
-    if install_deps {
-        project_generator::install_dependencies(&project_path)
-            .map_err(|e| Error::new(ErrorKind::Other, format!("An error occurred while installing dependencies: {}", e)))?;
-    }
+    project_generator::generate_project(template_path, project_path, install_deps)
+        .map_err(|e| Error::other(format!("An error occurred while generating the project: {}", e)))?;
 
     println!("Project generated successfully");
     Ok(())
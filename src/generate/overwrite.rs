@@ -0,0 +1,165 @@
+use std::fs;
+use std::io::{self, Error, ErrorKind, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use inquire::Select;
+use similar::TextDiff;
+
+use crate::utils::context::{self, OverwritePolicy};
+
+const OVERWRITE: &str = "Overwrite with generated file";
+const KEEP: &str = "Keep existing file";
+const OVERWRITE_ALL: &str = "Overwrite all remaining conflicts";
+const KEEP_ALL: &str = "Keep all remaining existing files";
+
+/// Fails when the project path is not a directory, or is not empty and the policy does not
+/// allow touching it or needs a terminal to prompt.
+pub fn check_target(project_path: &Path, policy: OverwritePolicy) -> io::Result<()> {
+    if !project_path.exists() {
+        return Ok(());
+    }
+
+    // No policy can merge a project into a file
+    if !project_path.is_dir() {
+        context::debug_print(&format!("ERROR: Target '{}' is not a directory", project_path.display()));
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!(
+                "'{}' already exists and is not a directory. Remove it or choose another project path",
+                project_path.display()
+            ),
+        ));
+    }
+
+    if matches!(policy, OverwritePolicy::SkipExisting | OverwritePolicy::Force) {
+        return Ok(());
+    }
+
+    if fs::read_dir(project_path)?.next().is_none() {
+        return Ok(());
+    }
+
+    // Checked before generating, not when the first conflict is found
+    if policy == OverwritePolicy::Prompt {
+        if io::stdin().is_terminal() {
+            return Ok(());
+        }
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "'{}' is not empty and conflicts cannot be confirmed without a terminal. Use --force or --skip-existing instead of --prompt-conflicts",
+                project_path.display()
+            ),
+        ));
+    }
+
+    context::debug_print(&format!("ERROR: Target '{}' already exists and is not empty", project_path.display()));
+    Err(Error::new(
+        ErrorKind::AlreadyExists,
+        format!(
+            "'{}' already exists and is not empty. Use --force to overwrite, --skip-existing to keep existing files or --prompt-conflicts to decide per file",
+            project_path.display()
+        ),
+    ))
+}
+
+/// Decides, file by file, whether a generated file replaces an existing one.
+pub struct ConflictResolver {
+    policy: OverwritePolicy,
+    remembered: Option<bool>,
+    /// Existing paths kept instead of the generated ones, relative to the project root.
+    skipped: Vec<PathBuf>,
+}
+
+impl ConflictResolver {
+    pub fn new(policy: OverwritePolicy) -> Self {
+        Self { policy, remembered: None, skipped: Vec::new() }
+    }
+
+    /// Returns true when `generated` should replace `existing`.
+    pub fn should_overwrite(&mut self, existing: &Path, generated: &Path, relative: &Path) -> io::Result<bool> {
+        let overwrite = self.decide(existing, generated, relative)?;
+        if !overwrite {
+            self.skipped.push(relative.to_path_buf());
+        }
+        Ok(overwrite)
+    }
+
+    pub fn into_skipped(self) -> Vec<PathBuf> {
+        self.skipped
+    }
+
+    fn decide(&mut self, existing: &Path, generated: &Path, relative: &Path) -> io::Result<bool> {
+        if is_identical(existing, generated) {
+            context::debug_print(&format!("Existing file is identical: {}", relative.display()));
+            return Ok(true);
+        }
+
+        match self.policy {
+            OverwritePolicy::Refuse | OverwritePolicy::Force => Ok(true),
+            OverwritePolicy::SkipExisting => {
+                println!("Skipping existing file: {}", relative.display());
+                Ok(false)
+            }
+            OverwritePolicy::Prompt => {
+                if let Some(overwrite) = self.remembered {
+                    return Ok(overwrite);
+                }
+                self.prompt(existing, generated, relative)
+            }
+        }
+    }
+
+    fn prompt(&mut self, existing: &Path, generated: &Path, relative: &Path) -> io::Result<bool> {
+        println!("\nConflict: {}", relative.display());
+        println!("{}", describe_difference(existing, generated, relative));
+
+        let choice = Select::new(
+            &format!("What should be done with '{}'?", relative.display()),
+            vec![OVERWRITE, KEEP, OVERWRITE_ALL, KEEP_ALL],
+        )
+        .prompt()
+        .map_err(|e| Error::new(ErrorKind::Interrupted, format!("Conflict resolution aborted: {}", e)))?;
+
+        Ok(match choice {
+            OVERWRITE => true,
+            KEEP => false,
+            OVERWRITE_ALL => {
+                self.remembered = Some(true);
+                true
+            }
+            _ => {
+                self.remembered = Some(false);
+                false
+            }
+        })
+    }
+}
+
+fn is_identical(existing: &Path, generated: &Path) -> bool {
+    if existing.is_dir() || generated.is_dir() {
+        return false;
+    }
+    match (fs::read(existing), fs::read(generated)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn describe_difference(existing: &Path, generated: &Path, relative: &Path) -> String {
+    if existing.is_dir() != generated.is_dir() {
+        return "One side is a directory, the other is a file".to_string();
+    }
+
+    match (fs::read_to_string(existing), fs::read_to_string(generated)) {
+        (Ok(old), Ok(new)) => {
+            let name = relative.display().to_string();
+            TextDiff::from_lines(&old, &new)
+                .unified_diff()
+                .context_radius(3)
+                .header(&format!("{} (existing)", name), &format!("{} (generated)", name))
+                .to_string()
+        }
+        _ => "Binary files differ".to_string(),
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use crate::utils::context;
+
+/// Toolchains whose dependencies can be installed after generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackageManager {
+    Pnpm,
+    Npm,
+    Yarn,
+    Bun,
+    Cargo,
+    Poetry,
+    Go,
+}
+
+impl PackageManager {
+    /// Detects the toolchain from the manifests and lockfiles of a generated project.
+    pub fn detect(project_path: &Path) -> Option<Self> {
+        let exists = |file: &str| project_path.join(file).exists();
+
+        if exists("package.json") {
+            // The `packageManager` field (corepack) wins over lockfiles
+            if let Some(manager) = from_package_json(&project_path.join("package.json")) {
+                return Some(manager);
+            }
+            return Some(if exists("pnpm-lock.yaml") {
+                Self::Pnpm
+            } else if exists("yarn.lock") {
+                Self::Yarn
+            } else if exists("bun.lockb") || exists("bun.lock") {
+                Self::Bun
+            } else if exists("package-lock.json") {
+                Self::Npm
+            } else {
+                Self::Pnpm
+            });
+        }
+
+        if exists("Cargo.toml") {
+            return Some(Self::Cargo);
+        }
+        if exists("poetry.lock") || is_poetry_project(&project_path.join("pyproject.toml")) {
+            return Some(Self::Poetry);
+        }
+        if exists("go.mod") {
+            return Some(Self::Go);
+        }
+        None
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "pnpm" => Some(Self::Pnpm),
+            "npm" => Some(Self::Npm),
+            "yarn" => Some(Self::Yarn),
+            "bun" => Some(Self::Bun),
+            "cargo" => Some(Self::Cargo),
+            "poetry" => Some(Self::Poetry),
+            "go" => Some(Self::Go),
+            _ => None,
+        }
+    }
+
+    pub fn install_command(&self) -> &'static str {
+        match self {
+            Self::Pnpm => "pnpm install",
+            Self::Npm => "npm install",
+            Self::Yarn => "yarn install",
+            Self::Bun => "bun install",
+            Self::Cargo => "cargo fetch",
+            Self::Poetry => "poetry install",
+            Self::Go => "go mod download",
+        }
+    }
+}
+
+fn from_package_json(path: &Path) -> Option<PackageManager> {
+    let content = fs::read_to_string(path).ok()?;
+    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let field = json.get("packageManager")?.as_str()?;
+    let name = field.split('@').next().unwrap_or(field);
+    context::debug_print(&format!("package.json declares packageManager '{}'", field));
+    PackageManager::from_name(name)
+}
+
+fn is_poetry_project(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.contains("[tool.poetry]"))
+        .unwrap_or(false)
+}
@@ -1,15 +1,22 @@
+use std::collections::HashSet;
 use std::fs;
-use std::io::{Error, ErrorKind};
-use std::path::Path;
-use std::process::Command;
+use std::io::{Error, ErrorKind, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::config::TemplateJson;
-use crate::generate::file_operations;
+use crate::config::{TemplateJson, TemplateMetadata, DEFAULT_INSTALL_TIMEOUT_SECS, INSTALL_ERROR_TAIL_LINES, MANIFEST_FILE};
+use crate::git;
+use crate::generate::{file_operations, hooks, overwrite, strict, trust};
+use crate::generate::manifest::Manifest;
+use crate::generate::package_manager::PackageManager;
+use crate::generate::staging::StagingDir;
 use crate::utils::strings;
+use crate::utils::command::{self, CapturedOutput};
 use crate::utils::context;
+use crate::utils::progress::Spinner;
 
-pub fn generate_project(template_path: &Path, project_path: &Path) -> std::io::Result<()> {
-    context::debug_print(&format!("Starting project generation"));
+pub fn generate_project(template_path: &Path, project_path: &Path, install_deps: bool) -> std::io::Result<()> {
+    context::debug_print("Starting project generation");
     context::debug_print(&format!("Template path: {}", template_path.display()));
     context::debug_print(&format!("Project path: {}", project_path.display()));
     
@@ -18,11 +25,40 @@ pub fn generate_project(template_path: &Path, project_path: &Path) -> std::io::R
         return Err(Error::new(ErrorKind::NotFound, "Template not found"));
     }
 
-    context::debug_print("Creating project directory");
-    fs::create_dir_all(&project_path)?;
+    overwrite::check_target(project_path, context::get_options().overwrite_policy)?;
+
+    // The template is copied and rendered in a staging directory that is only moved into place
+    // on success. Returning early drops the staging directory, which removes it.
+    context::debug_print("Creating staging directory");
+    let staging = StagingDir::new(project_path)?;
+
+    let metadata = strings::read_template_metadata(template_path)?;
+    // Read before the hooks run, it tells which variables they must not see
+    context::debug_print("Reading template configuration");
+    let config = strings::read_template_config(template_path)?;
+    context::debug_print(&format!("Found {} template configurations", config.len()));
+
+    let run_hooks = if metadata.hooks.is_empty() {
+        false
+    } else if context::get_options().no_hooks {
+        println!("Template hooks are disabled, skipping them");
+        false
+    } else {
+        trust::confirm_hooks(&metadata.hooks)?
+    };
+    if run_hooks {
+        hooks::run_hooks(
+            "pre-generate",
+            &metadata.hooks.pre_generate,
+            staging.path(),
+            project_path,
+            template_path,
+            &config,
+        )?;
+    }
     
     context::debug_print("Copying template files");
-    file_operations::copy_dir_all(&template_path, &project_path)?;
+    file_operations::copy_dir_all(template_path, staging.path())?;
 
     println!(
         "Project '{}' copied from template '{}' successfully",
@@ -30,54 +66,251 @@ pub fn generate_project(template_path: &Path, project_path: &Path) -> std::io::R
         template_path.file_name().unwrap().to_string_lossy()
     );
 
-    context::debug_print("Reading template configuration");
-    let config = strings::read_template_config(template_path)?;
-    context::debug_print(&format!("Found {} template configurations", config.len()));
-
     context::debug_print("Applying template configuration");
-    apply_template_config(&project_path, &config)?;
+    let mut issues = apply_template_config(staging.path(), &config)?;
+
+    if context::get_options().strict {
+        context::debug_print("Strict mode: checking generated files");
+        issues.extend(strict::find_unresolved_placeholders(staging.path())?);
+        issues.extend(strict::find_unused_variables(&config));
+        strict::check(issues)?;
+    }
+
+    let install_deps = install_deps && !context::get_options().skip_install;
+
+    // Built before installing so hashes only cover files coming from the template
+    let features = enabled_features(install_deps, run_hooks);
+    let mut manifest = Manifest::build(staging.path(), &config, features)?;
+
+    // Installed dependencies and hook outputs may record absolute paths, so they are produced
+    // in the final project directory. Until `finish`, a failure rolls the project back.
+    context::debug_print("Moving generated project into place");
+    let mut project = staging.commit()?;
+
+    // Existing files kept by the overwrite policy are the user's, not template output
+    context::debug_print("Writing generation manifest");
+    manifest.exclude(project.skipped());
+    project.protect(Path::new(MANIFEST_FILE))?;
+    manifest.write(project_path)?;
+
+    if install_deps {
+        install_dependencies(project_path, &metadata)?;
+    }
+
+    if run_hooks {
+        hooks::run_hooks(
+            "post-generate",
+            &metadata.hooks.post_generate,
+            project_path,
+            project_path,
+            template_path,
+            &config,
+        )?;
+    }
+
+    let options = context::get_options();
+    let git = options.git.or(&metadata.git);
+    if options.git_init.or(git.init).unwrap_or(false) {
+        if project_path.join(".git").exists() {
+            println!("'{}' is already a git repository, skipping git initialization", project_path.display());
+        } else {
+            git::init_repository(project_path, &git)?;
+            println!("Initialized git repository on branch '{}' with an initial commit", git.default_branch());
+        }
+    }
+    project.finish();
     
     context::debug_print("Project generation completed successfully");
     Ok(())
 }
 
-fn apply_template_config(project_path: &Path, config: &TemplateJson) -> std::io::Result<()> {
+/// Generation features recorded in the manifest.
+fn enabled_features(install_deps: bool, hooks: bool) -> Vec<String> {
+    let mut features = Vec::new();
+    if install_deps {
+        features.push("install".to_string());
+    }
+    if context::get_options().strict {
+        features.push("strict".to_string());
+    }
+    if hooks {
+        features.push("hooks".to_string());
+    }
+    features
+}
+
+/// Applies the replacements and returns the problems found along the way, used by strict mode.
+fn apply_template_config(project_path: &Path, config: &TemplateJson) -> std::io::Result<Vec<String>> {
     context::debug_print(&format!("Applying {} template configurations", config.len()));
+    let mut failures = Vec::new();
+    let mut issues = Vec::new();
+    // Discard problems left over from a previous generation
+    context::take_issues();
     
     for (i, file) in config.iter().enumerate() {
         context::debug_print(&format!("Processing configuration {}: {} files to replace", i + 1, file.files_to_replace.len()));
+        // The replacements are shared by every file of the configuration, one is only unused
+        // when none of the files contains it
+        let mut unmatched: Option<HashSet<String>> = None;
         
         for file_to_replace in &file.files_to_replace {
-            let file_path = project_path.join(&file_to_replace);
+            let file_path = project_path.join(file_to_replace);
             context::debug_print(&format!("Processing file: {}", file_path.display()));
             
-            if let Err(e) = file_operations::replace_in_file(&file_path, &file.replacements) {
-                context::debug_print(&format!("ERROR updating file {}: {}", file_to_replace, e));
-                println!("Error updating file {}: {}", file_to_replace, e);
-            } else {
-                context::debug_print(&format!("Successfully updated file: {}", file_to_replace));
+            let result = file_operations::replace_in_file(&file_path, &file.replacements, file.encoding.as_deref());
+            issues.extend(context::take_issues().into_iter().map(|issue| format!("{}: {}", file_to_replace, issue)));
+
+            match result {
+                Ok(file_unmatched) => {
+                    context::debug_print(&format!("Successfully updated file: {}", file_to_replace));
+                    let file_unmatched: HashSet<String> = file_unmatched.into_iter().collect();
+                    unmatched = Some(match unmatched {
+                        Some(unmatched) => &unmatched & &file_unmatched,
+                        None => file_unmatched,
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::NotFound => {
+                    // Templates may list optional files, a missing one is not fatal
+                    context::debug_print(&format!("File not found, skipping: {}", file_to_replace));
+                    println!("Warning: File {} listed in template configuration was not found", file_to_replace);
+                    issues.push(format!("{}: file listed in template configuration was not found", file_to_replace));
+                }
+                Err(e) => {
+                    context::debug_print(&format!("ERROR updating file {}: {}", file_to_replace, e));
+                    failures.push(format!("{}: {}", file_to_replace, e));
+                }
             }
         }
+
+        let unmatched = unmatched.unwrap_or_default();
+        for replacement in file.replacements.iter().filter(|r| unmatched.contains(&r.name)) {
+            issues.push(format!(
+                "No occurrence of '{{{{{}}}}}' or '{}' found in {}",
+                replacement.name,
+                replacement.key,
+                file.files_to_replace.join(", ")
+            ));
+        }
     }
-    Ok(())
+
+    if !failures.is_empty() {
+        return Err(Error::other(format!("Failed to update files:\n  {}", failures.join("\n  "))));
+    }
+    Ok(issues)
 }
 
-pub fn install_dependencies(project_path: &Path) -> std::io::Result<()> {
+/// Installs dependencies with, by priority, the `--install-command` option, the template
+/// metadata or the package manager detected from the generated files.
+///
+/// Output is captured: it is shown behind a spinner in interactive terminals, printed as it
+/// comes otherwise, or written to a log file in quiet mode. Installation is killed after
+/// the install timeout.
+pub fn install_dependencies(project_path: &Path, metadata: &TemplateMetadata) -> std::io::Result<()> {
     context::debug_print(&format!("Installing dependencies in: {}", project_path.display()));
-    
-    let status = Command::new("pnpm")
-        .arg("install")
-        .current_dir(project_path)
-        .status()?;
-
-    if !status.success() {
-        context::debug_print(&format!("ERROR: pnpm install failed with status: {}", status));
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Failed to install dependencies",
-        ));
+
+    let install_command = match resolve_install_command(project_path, metadata)? {
+        Some(install_command) => install_command,
+        None => {
+            println!("No package manager detected, skipping dependency installation");
+            return Ok(());
+        }
+    };
+
+    let options = context::get_options();
+    let timeout = match options.install_timeout.unwrap_or(DEFAULT_INSTALL_TIMEOUT_SECS) {
+        0 => Duration::MAX,
+        secs => Duration::from_secs(secs),
+    };
+    let message = format!("Installing dependencies with '{}'", install_command);
+
+    let mut spinner = (!options.quiet && std::io::stdout().is_terminal()).then(|| Spinner::new(&message));
+    if spinner.is_none() {
+        println!("{}", message);
+    }
+
+    let mut install = command::shell_command(&install_command);
+    install.current_dir(project_path);
+    let output = command::run_captured_with(&mut install, timeout, |lines, elapsed| match spinner.as_mut() {
+        Some(spinner) => spinner.tick(lines, elapsed),
+        None if !options.quiet => lines.iter().for_each(|line| println!("  | {}", line)),
+        None => {}
+    })
+    .map_err(|e| Error::new(e.kind(), format!("Failed to run '{}': {}", install_command, e)))?;
+    if let Some(spinner) = &spinner {
+        spinner.finish();
+    }
+
+    let log_path = if options.quiet || options.install_log.is_some() {
+        Some(write_install_log(&install_command, &output, options.install_log)?)
+    } else {
+        None
+    };
+
+    if !output.success() {
+        let reason = match output.status {
+            Some(status) => format!("exited with {}", status),
+            None => format!("timed out after {}s", timeout.as_secs()),
+        };
+        context::debug_print(&format!("ERROR: '{}' {}", install_command, reason));
+
+        let tail = &output.lines[output.lines.len().saturating_sub(INSTALL_ERROR_TAIL_LINES)..];
+        let mut error = format!("Failed to install dependencies: '{}' {}", install_command, reason);
+        if !tail.is_empty() {
+            error.push_str("\nLast output:");
+            tail.iter().for_each(|line| error.push_str(&format!("\n  | {}", line)));
+        }
+        if let Some(log_path) = log_path {
+            error.push_str(&format!("\nFull output: {}", log_path.display()));
+        }
+        return Err(Error::other(error));
+    }
+
+    println!("Dependencies installed");
+    if let Some(log_path) = log_path {
+        println!("Installation output written to {}", log_path.display());
     }
-    
     context::debug_print("Dependencies installed successfully");
     Ok(())
 }
+
+/// Writes the installation output to `log_path`, or to a file in the temp directory.
+fn write_install_log(install_command: &str, output: &CapturedOutput, log_path: Option<PathBuf>) -> std::io::Result<PathBuf> {
+    let log_path = log_path.unwrap_or_else(|| {
+        let project_name = context::get_variable("project_name").unwrap_or_else(|| "project".to_string());
+        std::env::temp_dir().join(format!("project-generator-install-{}.log", project_name))
+    });
+    context::debug_print(&format!("Writing installation log: {}", log_path.display()));
+
+    let status = match output.status {
+        Some(status) => status.to_string(),
+        None => "timed out".to_string(),
+    };
+    let mut content = format!("$ {}\n", install_command);
+    output.lines.iter().for_each(|line| content.push_str(&format!("{}\n", line)));
+    content.push_str(&format!("[{}]\n", status));
+
+    fs::write(&log_path, content)
+        .map_err(|e| Error::new(e.kind(), format!("Failed to write installation log '{}': {}", log_path.display(), e)))?;
+    Ok(log_path)
+}
+
+fn resolve_install_command(project_path: &Path, metadata: &TemplateMetadata) -> std::io::Result<Option<String>> {
+    if let Some(install_command) = context::get_options().install_command {
+        context::debug_print("Using install command from the command line");
+        return Ok(Some(install_command));
+    }
+    if let Some(install_command) = &metadata.install_command {
+        context::debug_print("Using install command from the template metadata");
+        return Ok(Some(install_command.clone()));
+    }
+    if let Some(name) = &metadata.package_manager {
+        let manager = PackageManager::from_name(name).ok_or_else(|| {
+            Error::new(ErrorKind::InvalidData, format!("Unknown package manager '{}' in template metadata", name))
+        })?;
+        return Ok(Some(manager.install_command().to_string()));
+    }
+
+    let detected = PackageManager::detect(project_path);
+    context::debug_print(&format!("Detected package manager: {:?}", detected));
+    Ok(detected.map(|manager| manager.install_command().to_string()))
+}
@@ -0,0 +1,254 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::generate::overwrite::ConflictResolver;
+use crate::utils::context;
+
+/// A directory next to the final project path where the project is built.
+///
+/// The staging directory is moved into place by `commit`. If it is dropped
+/// without being committed (because a generation step failed), it is removed,
+/// unless the `keep_staging` option is set.
+pub struct StagingDir {
+    path: PathBuf,
+    target: PathBuf,
+    /// Where existing files replaced by the commit are kept until the project is finished.
+    backup: PathBuf,
+    committed: bool,
+}
+
+impl StagingDir {
+    pub fn new(target: &Path) -> io::Result<Self> {
+        let parent = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let name = target
+            .file_name()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Project path has no directory name"))?
+            .to_string_lossy()
+            .to_string();
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis();
+
+        // Stage next to the target so the final move is a rename on the same filesystem
+        fs::create_dir_all(&parent)?;
+        let path = parent.join(format!(".{}.staging-{}", name, timestamp));
+        let backup = parent.join(format!(".{}.backup-{}", name, timestamp));
+        fs::create_dir_all(&path)?;
+        context::debug_print(&format!("Created staging directory: {}", path.display()));
+
+        Ok(Self {
+            path,
+            target: target.to_path_buf(),
+            backup,
+            committed: false,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Moves the staged project to its final location, resolving conflicts with
+    /// existing files according to the overwrite policy.
+    ///
+    /// The returned `CommittedProject` undoes the move when dropped before `finish`, so the
+    /// steps run in the final directory (installation, hooks) stay within the rollback.
+    pub fn commit(mut self) -> io::Result<CommittedProject> {
+        let created = !self.target.exists();
+        if created {
+            context::debug_print(&format!("Moving staging directory to '{}'", self.target.display()));
+            fs::rename(&self.path, &self.target)?;
+        }
+        self.committed = created;
+
+        let mut project = CommittedProject {
+            target: self.target.clone(),
+            created,
+            backup: self.backup.clone(),
+            moved: Vec::new(),
+            skipped: Vec::new(),
+            finished: false,
+        };
+        if !created {
+            context::debug_print(&format!("Merging staging directory into existing '{}'", self.target.display()));
+            // Every conflict is decided before anything is moved, an aborted prompt leaves the
+            // existing directory untouched
+            let mut resolver = ConflictResolver::new(context::get_options().overwrite_policy);
+            let mut moves = Vec::new();
+            plan_moves(&self.path, &self.target, Path::new(""), &mut resolver, &mut moves)?;
+            project.skipped = resolver.into_skipped();
+
+            for (relative, exists) in moves {
+                let target = self.target.join(&relative);
+                if exists {
+                    backup(&target, &project.backup.join(&relative))?;
+                }
+                fs::rename(self.path.join(&relative), &target)?;
+                project.moved.push((relative, exists));
+            }
+            fs::remove_dir_all(&self.path)?;
+            self.committed = true;
+        }
+        Ok(project)
+    }
+}
+
+/// A generated project moved into place, rolled back when dropped before `finish`.
+///
+/// A project directory created by the generation is removed. When the project was merged
+/// into an existing directory, the files it added are removed and the files it replaced,
+/// kept in a backup directory until then, are restored. With the `keep_staging` option the
+/// project is left as it is.
+pub struct CommittedProject {
+    target: PathBuf,
+    created: bool,
+    backup: PathBuf,
+    /// Paths moved into the existing target, relative to it, and whether they replaced one.
+    moved: Vec<(PathBuf, bool)>,
+    skipped: Vec<PathBuf>,
+    finished: bool,
+}
+
+impl CommittedProject {
+    /// Existing paths kept instead of the staged ones, relative to the project root.
+    pub fn skipped(&self) -> &[PathBuf] {
+        &self.skipped
+    }
+
+    /// Backs up `relative` before it is written in the project, outside of the staged files,
+    /// so a rollback removes it or restores the existing one.
+    pub fn protect(&mut self, relative: &Path) -> io::Result<()> {
+        if self.created {
+            return Ok(());
+        }
+        let target = self.target.join(relative);
+        let exists = target.symlink_metadata().is_ok();
+        if exists {
+            backup(&target, &self.backup.join(relative))?;
+        }
+        self.moved.push((relative.to_path_buf(), exists));
+        Ok(())
+    }
+
+    /// Keeps the project and discards the backup of the replaced files.
+    pub fn finish(mut self) {
+        self.finished = true;
+        if self.backup.exists() {
+            context::debug_print(&format!("Removing backup directory: {}", self.backup.display()));
+            if let Err(e) = fs::remove_dir_all(&self.backup) {
+                eprintln!("Warning: Failed to clean up backup directory '{}': {}", self.backup.display(), e);
+            }
+        }
+    }
+
+    fn rollback(&mut self) -> io::Result<()> {
+        if self.created {
+            context::debug_print(&format!("Removing project directory: {}", self.target.display()));
+            return fs::remove_dir_all(&self.target);
+        }
+        for (relative, replaced) in self.moved.iter().rev() {
+            let target = self.target.join(relative);
+            if target.symlink_metadata().is_ok() {
+                remove_path(&target)?;
+            }
+            if *replaced {
+                fs::rename(self.backup.join(relative), &target)?;
+            }
+        }
+        if self.backup.exists() {
+            fs::remove_dir_all(&self.backup)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for CommittedProject {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        if context::get_options().keep_staging {
+            println!("Generation failed, generated project kept at: {}", self.target.display());
+            if self.backup.exists() {
+                println!("Replaced files are backed up in: {}", self.backup.display());
+            }
+            return;
+        }
+
+        if let Err(e) = self.rollback() {
+            eprintln!("Warning: Failed to roll back the generated project in '{}': {}", self.target.display(), e);
+            if self.backup.exists() {
+                eprintln!("Replaced files are backed up in: {}", self.backup.display());
+            }
+        }
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if self.committed || !self.path.exists() {
+            return;
+        }
+
+        if context::get_options().keep_staging {
+            println!("Generation failed, staging directory kept at: {}", self.path.display());
+            return;
+        }
+
+        context::debug_print(&format!("Removing staging directory: {}", self.path.display()));
+        if let Err(e) = fs::remove_dir_all(&self.path) {
+            eprintln!("Warning: Failed to clean up staging directory '{}': {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Lists the staged paths to move into the existing `dst`, relative to the roots, and whether
+/// they replace an existing path. Directories present on both sides are merged.
+fn plan_moves(
+    src: &Path,
+    dst: &Path,
+    relative: &Path,
+    resolver: &mut ConflictResolver,
+    moves: &mut Vec<(PathBuf, bool)>,
+) -> io::Result<()> {
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let target = dst.join(entry.file_name());
+        let relative = relative.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() && target.is_dir() {
+            plan_moves(&entry.path(), &target, &relative, resolver, moves)?;
+            continue;
+        }
+
+        let exists = target.symlink_metadata().is_ok();
+        if exists && !resolver.should_overwrite(&target, &entry.path(), &relative)? {
+            continue;
+        }
+        moves.push((relative, exists));
+    }
+    Ok(())
+}
+
+fn backup(path: &Path, backup: &Path) -> io::Result<()> {
+    if let Some(parent) = backup.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(path, backup)
+}
+
+fn remove_path(path: &Path) -> io::Result<()> {
+    if path.is_dir() && !path.is_symlink() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
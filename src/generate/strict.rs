@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Error, ErrorKind};
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::config::{TemplateJson, GENERATOR_VARIABLES};
+use crate::utils::context;
+
+/// Lists every `{{placeholder}}` left in the generated text files.
+pub fn find_unresolved_placeholders(project_path: &Path) -> io::Result<Vec<String>> {
+    let regex = Regex::new(r"\{\{([A-Za-z_][A-Za-z0-9_]*)\}\}").unwrap();
+    let mut issues = Vec::new();
+    scan_dir(project_path, project_path, &regex, &mut issues)?;
+    Ok(issues)
+}
+
+fn scan_dir(root: &Path, dir: &Path, regex: &Regex, issues: &mut Vec<String>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            scan_dir(root, &path, regex, issues)?;
+            continue;
+        }
+        if !file_type.is_file() {
+            continue;
+        }
+
+        // Binary and non UTF-8 files cannot contain placeholders we would have rendered
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let relative = path.strip_prefix(root).unwrap_or(&path).display().to_string();
+
+        for (line_number, line) in content.lines().enumerate() {
+            for capture in regex.captures_iter(line) {
+                let whole = capture.get(0).unwrap();
+                // `${{ ... }}` is GitHub Actions syntax, not one of our placeholders
+                if line[..whole.start()].ends_with('$') {
+                    continue;
+                }
+                issues.push(format!("{}:{}: unresolved placeholder '{}'", relative, line_number + 1, whole.as_str()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Lists variables that were provided but that no replacement of the template uses.
+pub fn find_unused_variables(config: &TemplateJson) -> Vec<String> {
+    let used: HashSet<&str> = config
+        .iter()
+        .flat_map(|c| c.replacements.iter().map(|r| r.name.as_str()))
+        .collect();
+
+    let mut unused: Vec<String> = context::get_variable_names()
+        .into_iter()
+        .filter(|name| !used.contains(name.as_str()) && !GENERATOR_VARIABLES.contains(&name.as_str()))
+        .collect();
+    unused.sort();
+
+    unused
+        .into_iter()
+        .map(|name| format!("Variable '{}' is not used by the template", name))
+        .collect()
+}
+
+/// Fails with a single consolidated report when strict mode found any problem.
+pub fn check(issues: Vec<String>) -> io::Result<()> {
+    let mut seen = HashSet::new();
+    let issues: Vec<String> = issues.into_iter().filter(|i| seen.insert(i.clone())).collect();
+
+    if issues.is_empty() {
+        context::debug_print("Strict mode: no problems found");
+        return Ok(());
+    }
+
+    Err(Error::new(
+        ErrorKind::InvalidData,
+        format!(
+            "Strict mode found {} problem(s):\n  - {}",
+            issues.len(),
+            issues.join("\n  - ")
+        ),
+    ))
+}
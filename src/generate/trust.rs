@@ -0,0 +1,153 @@
+use std::env;
+use std::fs;
+use std::io::{self, Error, ErrorKind, IsTerminal};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use inquire::Select;
+
+use crate::config::{TemplateHooks, TRUSTED_TEMPLATES_FILE};
+use crate::template::TemplateSource;
+use crate::utils::context;
+
+const RUN_ONCE: &str = "Run the hooks this time";
+const RUN_AND_TRUST: &str = "Run the hooks and trust this template commit";
+const SKIP: &str = "Skip the hooks";
+const ABORT: &str = "Abort generation";
+
+/// User-level allowlist of template commits whose hooks run without confirmation.
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct TrustedTemplates {
+    #[serde(default)]
+    templates: Vec<TrustedTemplate>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct TrustedTemplate {
+    repository: String,
+    commit: String,
+    /// Seconds since the Unix epoch.
+    trusted_at: u64,
+}
+
+/// Decides whether the template hooks may run.
+///
+/// Hooks of a trusted template commit run directly. Otherwise they are listed and the user
+/// chooses to run them, trust the commit, skip them or abort. Without a terminal, untrusted
+/// hooks are refused unless `--trust-hooks` is set.
+pub fn confirm_hooks(hooks: &TemplateHooks) -> io::Result<bool> {
+    if context::get_options().trust_hooks {
+        context::debug_print("Hooks trusted from the command line");
+        return Ok(true);
+    }
+
+    let source = context::get_template_source();
+    if let Some(source) = &source {
+        if is_trusted(source)? {
+            context::debug_print(&format!("Template commit {} is trusted", source.commit));
+            return Ok(true);
+        }
+    }
+
+    let origin = match &source {
+        Some(source) => format!("'{}/{}' from {} at {}", source.category, source.name, source.repository, source.commit),
+        None => "of unknown origin".to_string(),
+    };
+    println!("\nThe template {} declares hooks that run commands on this machine:", origin);
+    for (stage, stage_hooks) in [("pre-generate", &hooks.pre_generate), ("post-generate", &hooks.post_generate)] {
+        for hook in stage_hooks {
+            println!("  [{}] {}", stage, hook.command);
+        }
+    }
+
+    if !io::stdin().is_terminal() {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            "The template is not trusted and its hooks cannot be confirmed without a terminal. Use --trust-hooks to run them or --no-hooks to skip them",
+        ));
+    }
+
+    let mut choices = vec![RUN_ONCE];
+    if source.is_some() {
+        choices.push(RUN_AND_TRUST);
+    }
+    choices.extend([SKIP, ABORT]);
+
+    let choice = Select::new("Run these hooks?", choices)
+        .prompt()
+        .map_err(|e| Error::new(ErrorKind::Interrupted, format!("Hook confirmation aborted: {}", e)))?;
+
+    match choice {
+        RUN_ONCE => Ok(true),
+        RUN_AND_TRUST => {
+            if let Some(source) = &source {
+                trust(source)?;
+            }
+            Ok(true)
+        }
+        SKIP => {
+            println!("Skipping template hooks");
+            Ok(false)
+        }
+        _ => Err(Error::new(ErrorKind::Interrupted, "Generation aborted, template hooks were not accepted")),
+    }
+}
+
+fn is_trusted(source: &TemplateSource) -> io::Result<bool> {
+    Ok(read_trusted()?
+        .templates
+        .iter()
+        .any(|t| t.repository == source.repository && t.commit == source.commit))
+}
+
+/// Records the template commit in the allowlist.
+fn trust(source: &TemplateSource) -> io::Result<()> {
+    let path = trusted_templates_path().ok_or_else(|| {
+        Error::new(ErrorKind::NotFound, "No user configuration directory found to record trusted templates")
+    })?;
+
+    let mut trusted = read_trusted()?;
+    trusted.templates.push(TrustedTemplate {
+        repository: source.repository.clone(),
+        commit: source.commit.clone(),
+        trusted_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    });
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut content = serde_json::to_string_pretty(&trusted)?;
+    content.push('\n');
+    fs::write(&path, content)?;
+    println!("Template commit {} is now trusted ({})", source.commit, path.display());
+    Ok(())
+}
+
+fn read_trusted() -> io::Result<TrustedTemplates> {
+    let path = match trusted_templates_path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(TrustedTemplates::default()),
+    };
+    context::debug_print(&format!("Reading trusted templates: {}", path.display()));
+
+    let content = fs::read_to_string(&path)?;
+    serde_json::from_str(&content).map_err(|e| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid trusted templates file '{}': {}", path.display(), e),
+        )
+    })
+}
+
+/// `TRUSTED_TEMPLATES_FILE` in `$XDG_CONFIG_HOME`, `~/.config` or `%APPDATA%`.
+fn trusted_templates_path() -> Option<PathBuf> {
+    let config_dir = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(config_dir.join(TRUSTED_TEMPLATES_FILE))
+}
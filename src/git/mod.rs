@@ -0,0 +1,59 @@
+use std::io::{Error, Result};
+use std::path::Path;
+
+use git2::{Commit, IndexAddOption, Oid, Repository, RepositoryInitOptions, Signature, Status, StatusOptions};
+
+use crate::config::GitSettings;
+use crate::utils::context;
+
+/// Initializes a repository in `path` on the configured default branch and commits every
+/// file not excluded by the project's `.gitignore`.
+pub fn init_repository(path: &Path, settings: &GitSettings) -> Result<Repository> {
+    let repo = init_empty(path, settings.default_branch())?;
+    commit_all(&repo, settings, None).map_err(|e| Error::other(format!("Failed to create initial commit: {}", e)))?;
+    Ok(repo)
+}
+
+/// Initializes a repository without any commit, `branch` being the branch of the first one.
+pub fn init_empty(path: &Path, branch: &str) -> Result<Repository> {
+    context::debug_print(&format!("Initializing git repository in '{}' on branch '{}'", path.display(), branch));
+
+    let mut init_options = RepositoryInitOptions::new();
+    init_options.initial_head(branch);
+    Repository::init_opts(path, &init_options)
+        .map_err(|e| Error::other(format!("Failed to initialize git repository: {}", e)))
+}
+
+/// Commits every file of the working directory on HEAD. With a parent, the commit starts from
+/// the parent's tree, so its files that are not in the working directory are kept.
+pub fn commit_all(
+    repo: &Repository,
+    settings: &GitSettings,
+    parent: Option<&Commit>,
+) -> std::result::Result<Oid, git2::Error> {
+    let mut index = repo.index()?;
+    match parent {
+        Some(parent) => {
+            // add_all would also stage the deletion of the parent's files missing here
+            index.read_tree(&parent.tree()?)?;
+            index.write()?;
+            let mut options = StatusOptions::new();
+            options.include_untracked(true).recurse_untracked_dirs(true);
+            let changed = Status::WT_NEW | Status::WT_MODIFIED | Status::WT_TYPECHANGE;
+            for entry in repo.statuses(Some(&mut options))?.iter() {
+                if let (true, Some(path)) = (entry.status().intersects(changed), entry.path()) {
+                    index.add_path(Path::new(path))?;
+                }
+            }
+        }
+        // Ignored files are skipped unless IndexAddOption::FORCE is given
+        None => index.add_all(["*"], IndexAddOption::DEFAULT, None)?,
+    }
+    index.write()?;
+    context::debug_print(&format!("Staged {} files", index.len()));
+
+    let tree = repo.find_tree(index.write_tree()?)?;
+    let signature = Signature::now(settings.author_name(), settings.author_email())?;
+    let parents: Vec<&Commit> = parent.into_iter().collect();
+    repo.commit(Some("HEAD"), &signature, &signature, settings.commit_message(), &tree, &parents)
+}
@@ -1,167 +1,224 @@
-use git2::{Repository, Signature, Cred, RemoteCallbacks};
-use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION, ACCEPT, USER_AGENT};
+use async_trait::async_trait;
 use serde_json::json;
-use std::path::Path;
-use crate::config::REPO_URL;
+use crate::config::github_config::{BranchProtection, Permission, RepositorySettings};
+use crate::config::provider_config::{LabelDefinition, ProviderKind, ServerSettings};
+use crate::github::secrets;
+use crate::provider::client::{encode_path_segment, ApiClient};
+use crate::provider::{ExistingRepository, Provider, ProviderResult};
+
+const SERVICE: &str = "GitHub";
 
 pub struct GitHubRepo {
+    client: ApiClient,
     token: String,
+    api_url: String,
+    url: String,
+    organization: String,
 }
 
 impl GitHubRepo {
-    pub fn new(token: &str) -> Self {
-        Self { 
-            token: token.to_string() 
+    pub fn new(token: &str, server: &ServerSettings, organization: &str) -> Self {
+        Self {
+            client: ApiClient::new(SERVICE, token, "application/vnd.github.v3+json"),
+            token: token.to_string(),
+            api_url: server.api_url(ProviderKind::GitHub),
+            url: server.url(ProviderKind::GitHub).to_string(),
+            organization: organization.to_string(),
         }
     }
 
-    pub async fn create_repository(
+    /// API URL of an organization resource, `path` starting with a slash.
+    fn org_endpoint(&self, path: &str) -> String {
+        format!("{}/orgs/{}{}", self.api_url, self.organization, path)
+    }
+
+    /// API URL of a repository resource, `path` empty or starting with a slash.
+    fn repo_endpoint(&self, name: &str, path: &str) -> String {
+        format!("{}/repos/{}/{}{}", self.api_url, self.organization, name, path)
+    }
+}
+
+#[async_trait]
+impl Provider for GitHubRepo {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::GitHub
+    }
+
+    fn token(&self) -> &str {
+        &self.token
+    }
+
+    fn remote_url(&self, name: &str) -> String {
+        format!("{}/{}/{}.git", self.url, self.organization, name)
+    }
+
+    async fn create_repository(
         &self,
         name: &str,
         description: &str,
-        private: bool,
-        topic: Option<&str>,
-    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-        // Extract organization from REPO_URL constant
-        // REPO_URL = "https://github.com/NextNodeSolutions"
-        let org_name = REPO_URL
-            .split('/')
-            .last()
-            .ok_or("Could not extract organization from REPO_URL")?;
-        
-        // Build headers
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", self.token))
-                .map_err(|_| "Failed to create authorization header")?,
-        );
-        headers.insert(
-            ACCEPT,
-            HeaderValue::from_str("application/vnd.github.v3+json")
-                .map_err(|_| "Failed to create accept header")?,
-        );
-        headers.insert(
-            USER_AGENT,
-            HeaderValue::from_str("NextNode-Project-Generator/1.0")
-                .map_err(|_| "Failed to create user-agent header")?,
-        );
-
-        // Build request body
-        let body = json!({
-            "name": name,
-            "description": description,
-            "private": private,
-            "auto_init": false
-        });
-
-        // Make GitHub API call to create repository
-        let client = reqwest::Client::new();
-        let response = client
-            .post(&format!("https://api.github.com/orgs/{}/repos", org_name))
-            .headers(headers.clone())
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| format!("Failed to send request to GitHub API: {}", e))?;
-
-        if !response.status().is_success() {
-            let error = response.text().await
-                .map_err(|e| format!("Failed to read error response: {}", e))?;
-            return Err(format!("GitHub API error: {}", error).into());
-        }
-
-        let repo_data: serde_json::Value = response.json().await
+        settings: &RepositorySettings,
+    ) -> ProviderResult<String> {
+        // Build request body, with the visibility and settings from the config
+        let mut body = settings.creation_fields();
+        body.insert("name".to_string(), json!(name));
+        body.insert("description".to_string(), json!(description));
+        body.insert("auto_init".to_string(), json!(false));
+
+        let request = self.client.post(self.org_endpoint("/repos")).json(&body);
+        let repo_data: serde_json::Value = self.client.send(request).await?.json().await
             .map_err(|e| format!("Failed to parse response: {}", e))?;
-        
+
         let repo_url = repo_data["html_url"]
             .as_str()
             .ok_or("No html_url in response")?
             .to_string();
+        Ok(repo_url)
+    }
 
-        // Add topic if provided
-        if let Some(topic_name) = topic {
-            println!("Adding topic '{}' to repository...", topic_name);
-            
-            let topics_body = json!({
-                "names": [topic_name]
-            });
-
-            let topics_response = client
-                .put(&format!("https://api.github.com/repos/{}/{}/topics", org_name, name))
-                .headers(headers)
-                .json(&topics_body)
-                .send()
-                .await
-                .map_err(|e| format!("Failed to add topic: {}", e))?;
-
-            if !topics_response.status().is_success() {
-                let error = topics_response.text().await
-                    .map_err(|e| format!("Failed to read topics error response: {}", e))?;
-                // Don't fail the entire operation for topic addition failure, just warn
-                eprintln!("Warning: Failed to add topic '{}': {}", topic_name, error);
-            } else {
-                println!("Successfully added topic '{}' to repository", topic_name);
-            }
-        }
+    /// Deletes a repository, which needs the `delete_repo` scope.
+    async fn delete_repository(&self, name: &str) -> ProviderResult<()> {
+        let request = self.client.delete(self.repo_endpoint(name, ""));
+        self.client.send(request).await?;
+        Ok(())
+    }
 
-        Ok(repo_url)
+    async fn get_repository(&self, name: &str) -> ProviderResult<Option<ExistingRepository>> {
+        let Some(response) = self.client.get_optional(self.repo_endpoint(name, "")).await? else {
+            return Ok(None);
+        };
+        let repo_data: serde_json::Value = response.json().await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        let web_url = repo_data["html_url"].as_str().ok_or("No html_url in response")?.to_string();
+        let default_branch = repo_data["default_branch"].as_str().unwrap_or("main").to_string();
+
+        // A repository without commits has no branch
+        let request = self.client.get(self.repo_endpoint(name, "/branches?per_page=1"));
+        let branches: Vec<serde_json::Value> = self.client.send(request).await?.json().await
+            .map_err(|e| format!("Failed to parse branches response: {}", e))?;
+
+        Ok(Some(ExistingRepository {
+            web_url,
+            default_branch,
+            empty: branches.is_empty(),
+        }))
     }
 
-    pub fn initialize_git_and_push(
-        &self,
-        local_path: &Path,
-        repo_url: &str,
-        author_name: &str,
-        author_email: &str,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Remove existing .git directory if it exists
-        let git_dir = local_path.join(".git");
-        if git_dir.exists() {
-            std::fs::remove_dir_all(&git_dir)?;
+    async fn update_settings(&self, name: &str, description: &str, settings: &RepositorySettings) -> ProviderResult<()> {
+        let mut body = settings.creation_fields();
+        body.insert("description".to_string(), json!(description));
+        let request = self.client.patch(self.repo_endpoint(name, "")).json(&body);
+        self.client.send(request).await?;
+        Ok(())
+    }
+
+    async fn set_topics(&self, name: &str, topics: &[String]) -> ProviderResult<()> {
+        let request = self
+            .client
+            .put(self.repo_endpoint(name, "/topics"))
+            .json(&json!({ "names": topics }));
+        self.client.send(request).await?;
+        Ok(())
+    }
+
+    async fn set_labels(&self, name: &str, labels: &[LabelDefinition]) -> ProviderResult<()> {
+        for label in labels {
+            let endpoint = self.repo_endpoint(name, &format!("/labels/{}", encode_path_segment(&label.name)));
+            let fields = json!({ "color": label.hex_color(), "description": label.description() });
+            let request = match self.client.get_optional(endpoint.clone()).await? {
+                Some(_) => self.client.patch(endpoint).json(&fields),
+                None => self
+                    .client
+                    .post(self.repo_endpoint(name, "/labels"))
+                    .json(&json!({ "name": label.name, "color": label.hex_color(), "description": label.description() })),
+            };
+            self.client.send(request).await?;
         }
+        Ok(())
+    }
+
+    async fn open_pull_request(
+        &self,
+        name: &str,
+        head: &str,
+        base: &str,
+        title: &str,
+        body: &str,
+    ) -> ProviderResult<String> {
+        let request = self
+            .client
+            .post(self.repo_endpoint(name, "/pulls"))
+            .json(&json!({ "title": title, "head": head, "base": base, "body": body }));
+        let pull_request: serde_json::Value = self.client.send(request).await?.json().await
+            .map_err(|e| format!("Failed to parse pull request response: {}", e))?;
+        Ok(pull_request["html_url"].as_str().ok_or("No html_url in pull request response")?.to_string())
+    }
 
-        // 1. git init
-        let repo = Repository::init(local_path)?;
-        
-        // 2. git branch -M main (la branche main est créée par défaut avec git2)
-        // Note: git2 crée automatiquement la branche main lors du premier commit
-        
-        // 3. À ce stade, pnpm install a déjà été fait avant d'appeler cette fonction
-        
-        // 4. git add .
-        let mut index = repo.index()?;
-        index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
-        index.write()?;
-
-        // 5. git commit -m "first commit"
-        let tree_id = index.write_tree()?;
-        let tree = repo.find_tree(tree_id)?;
-        let signature = Signature::now(author_name, author_email)?;
-        
-        repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "first commit",
-            &tree,
-            &[],
-        )?;
-
-        // 6. git remote add origin <url>
-        let mut remote = repo.remote("origin", repo_url)?;
-
-        // 7. git push -u origin main (utiliser HEAD pour éviter les problèmes de référence)
-        let mut callbacks = RemoteCallbacks::new();
-        let token = self.token.clone();
-        callbacks.credentials(move |_url, username_from_url, _allowed_types| {
-            Cred::userpass_plaintext(username_from_url.unwrap_or("git"), &token)
-        });
-
-        let mut push_options = git2::PushOptions::new();
-        push_options.remote_callbacks(callbacks);
-        remote.push(&["HEAD:refs/heads/main"], Some(&mut push_options))?;
+    async fn update_default_branch(&self, name: &str, branch: &str) -> ProviderResult<()> {
+        let request = self
+            .client
+            .patch(self.repo_endpoint(name, ""))
+            .json(&json!({ "default_branch": branch }));
+        self.client.send(request).await?;
+        Ok(())
+    }
+
+    /// Protects a pushed branch, replacing any existing protection.
+    async fn protect_branch(&self, name: &str, branch: &str, protection: &BranchProtection) -> ProviderResult<()> {
+        let request = self
+            .client
+            .put(self.repo_endpoint(name, &format!("/branches/{}/protection", encode_path_segment(branch))))
+            .json(&protection.request_body());
+        self.client.send(request).await?;
+        Ok(())
+    }
+
+    /// Gives an organization team access to the repository.
+    async fn add_team(&self, name: &str, slug: &str, permission: Permission) -> ProviderResult<()> {
+        let request = self
+            .client
+            .put(self.org_endpoint(&format!("/teams/{}/repos/{}/{}", slug, self.organization, name)))
+            .json(&json!({ "permission": permission.to_string() }));
+        self.client.send(request).await?;
+        Ok(())
+    }
+
+    /// Gives a user access to the repository. Returns true when an invitation was sent,
+    /// false when the user already had access through the organization.
+    async fn add_collaborator(&self, name: &str, username: &str, permission: Permission) -> ProviderResult<bool> {
+        let request = self
+            .client
+            .put(self.repo_endpoint(name, &format!("/collaborators/{}", username)))
+            .json(&json!({ "permission": permission.to_string() }));
+        let response = self.client.send(request).await?;
+        Ok(response.status() == reqwest::StatusCode::CREATED)
+    }
+
+    /// Creates or replaces an Actions secret, encrypted with the repository public key.
+    async fn set_secret(&self, name: &str, secret_name: &str, value: &str) -> ProviderResult<()> {
+        let request = self.client.get(self.repo_endpoint(name, "/actions/secrets/public-key"));
+        let public_key: serde_json::Value = self.client.send(request).await?.json().await
+            .map_err(|e| format!("Failed to parse public key response: {}", e))?;
+        let key = public_key["key"].as_str().ok_or("No key in public key response")?;
+        let key_id = public_key["key_id"].as_str().ok_or("No key_id in public key response")?;
+
+        let request = self
+            .client
+            .put(self.repo_endpoint(name, &format!("/actions/secrets/{}", secret_name)))
+            .json(&json!({
+                "encrypted_value": secrets::encrypt_secret(key, value)?,
+                "key_id": key_id,
+            }));
+        self.client.send(request).await?;
+        Ok(())
+    }
 
+    /// Creates an Actions variable.
+    async fn create_variable(&self, name: &str, variable_name: &str, value: &str) -> ProviderResult<()> {
+        let request = self
+            .client
+            .post(self.repo_endpoint(name, "/actions/variables"))
+            .json(&json!({ "name": variable_name, "value": value }));
+        self.client.send(request).await?;
         Ok(())
     }
 }
@@ -0,0 +1,21 @@
+use std::io::{Error, ErrorKind, Result};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crypto_box::aead::OsRng;
+use crypto_box::PublicKey;
+
+/// Encrypts a secret for the GitHub Actions secrets API: a libsodium sealed box for the
+/// repository public key, base64-encoded.
+pub fn encrypt_secret(public_key: &str, value: &str) -> Result<String> {
+    let key_bytes = STANDARD
+        .decode(public_key)
+        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("Invalid repository public key: {}", e)))?;
+    let public_key = PublicKey::from_slice(&key_bytes)
+        .map_err(|_| Error::new(ErrorKind::InvalidData, "Invalid repository public key length"))?;
+
+    let sealed = public_key
+        .seal(&mut OsRng, value.as_bytes())
+        .map_err(|_| Error::other("Failed to encrypt secret"))?;
+    Ok(STANDARD.encode(sealed))
+}
@@ -2,8 +2,11 @@ pub mod args;
 pub mod cli;
 pub mod config;
 pub mod generate;
+pub mod git;
 pub mod github;
+pub mod provider;
 pub mod template;
+pub mod update;
 pub mod utils;
 
 pub use cli::interact;
@@ -2,24 +2,61 @@ mod args;
 mod cli;
 mod config;
 mod generate;
+mod git;
 mod github;
+mod provider;
 mod template;
+mod update;
 mod utils;
 
 use std::io::{Error, ErrorKind, Result};
-use args::Args;
+use args::{Args, Command};
 use clap::Parser;
 use cli::{get_template_info, prompt_for_repo_name};
 use generate::{handle_config_mode, handle_interactive_mode};
-use github::{create_github_repository_with_code, extract_organization_from_repo_url};
+use config::provider_config::ProviderKind;
+use provider::plan::RepositoryPlan;
+use provider::remote::{self, create_repository_with_code, extract_organization_from_repo_url};
 use template::TemplateManager;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let args = Args::parse();
 
-    // Set debug mode in the global context
+    // Set debug mode and generation options in the global context
     utils::context::set_debug_mode(args.debug);
+    utils::context::set_options(utils::context::GenerationOptions {
+        keep_staging: args.keep_staging,
+        overwrite_policy: args.overwrite_policy(),
+        strict: args.strict,
+        skip_install: args.skip_install,
+        install_command: args.install_command.clone(),
+        install_timeout: args.install_timeout,
+        // Remote mode output is usually read as logs, installation output would drown it
+        quiet: args.quiet || args.remote,
+        install_log: args.install_log.clone(),
+        // Remote mode initializes the repository itself before pushing
+        git_init: if args.remote { Some(false) } else { args.git_init.then_some(true) },
+        git: Default::default(),
+        no_hooks: args.no_hooks,
+        trust_hooks: args.trust_hooks,
+        hook_environment: Default::default(),
+    });
+
+    // Subcommands work on an existing project and clone the templates themselves
+    match &args.command {
+        Some(Command::Update { project_dir, reference }) => {
+            return update::handle_update(project_dir, reference.as_deref());
+        }
+        Some(Command::Regenerate { project_dir, output, reference, variables }) => {
+            return update::handle_regenerate(project_dir, output.as_deref(), reference.as_deref(), variables);
+        }
+        Some(Command::RetryPush { project_dir }) => {
+            // The provider, and so the token variable, comes from the saved plan
+            return provider::retry::handle_retry_push(project_dir, args.token.clone()).await;
+        }
+        None => {}
+    }
 
     // Get template branch from config if available
     let template_branch = if let Some(config_path) = &args.config {
@@ -37,12 +74,13 @@ async fn main() -> Result<()> {
     // Get template info and path
     let (category, template_name) = get_template_info(&args, &template_manager)?;
     let template_path = template_manager.get_template_path(&category, &template_name);
+    utils::context::set_template_source(template_manager.get_source(&category, &template_name));
 
     // Handle local generation first (early return)
     if !args.remote {
         // Handle generation based on mode
         if args.config.is_none() {
-            return handle_interactive_mode(&template_path).map_err(|e| Error::new(ErrorKind::Other, e.to_string()));
+            return handle_interactive_mode(&template_path).map_err(|e| Error::other(e.to_string()));
         }
 
         // Get project name from variables
@@ -50,80 +88,170 @@ async fn main() -> Result<()> {
             utils::error::print_error_and_exit("project_name is required in configuration file")
         });
 
-        return handle_config_mode(&template_path, &project_name).map_err(|e| Error::new(ErrorKind::Other, e.to_string()));
+        return handle_config_mode(&template_path, &project_name).map_err(|e| Error::other(e.to_string()));
     }
 
-    // Remote mode: generate project locally, then create GitHub repo
-    let token = args
-        .token
-        .or_else(|| std::env::var("GITHUB_TOKEN").ok())
-        .ok_or_else(|| {
-            Error::new(
-                ErrorKind::InvalidInput,
-                "GitHub token is required for remote mode. Set GITHUB_TOKEN env var or use --token"
-            )
-        })?;
-
+    // Remote mode: generate project locally, then create the repository on the provider
+    
     // Config file is required for remote mode - check early
     let config_path = args.config.as_ref().ok_or_else(|| {
         Error::new(ErrorKind::InvalidInput, "Config file is required for remote mode. Use --config to specify a config file.")
     })?;
 
-    // Read and parse config file early to get project name and validate github_tag
+    // Read and parse config file early to get project name and validate the repository settings
     let file_config = crate::config::file_config::from_file(config_path)
         .map_err(|e| Error::new(ErrorKind::InvalidData, format!("Failed to read config file: {}", e)))?;
     
-    // Validate github_tag early (before pulling code)
-    file_config.validate_github_tag()
-        .map_err(|e| Error::new(ErrorKind::InvalidData, format!("GitHub tag validation failed: {}", e)))?;
+    // Validate repository settings early (before generating code)
+    let provider = file_config.provider;
+    file_config.server.validate(provider)?;
+    file_config.repository.validate()?;
+    let template_metadata = utils::strings::read_template_metadata(&template_path)?;
+    let branch_protection = match (&file_config.github_branch_protection, &template_metadata.branch_protection) {
+        (Some(protection), _) => Some(protection.clone()),
+        // Templates are shared by every provider, their GitHub protection is not an error elsewhere
+        (None, Some(_)) if provider != ProviderKind::GitHub => {
+            println!("Note: The template's branch protection only applies on GitHub, it is skipped on {}", provider);
+            None
+        }
+        (None, protection) => protection.clone(),
+    };
+    if let Some(protection) = &branch_protection {
+        protection.validate()?;
+    }
+    crate::config::provider_config::validate_labels(&file_config.labels)?;
+    crate::config::github_config::validate_access(&file_config.github_teams, &file_config.github_collaborators)?;
+    // Secret and variable values are read now so a missing one stops before generating
+    let secrets = file_config.github_secrets.iter().map(|secret| secret.resolve()).collect::<Result<Vec<_>>>()?;
+    let variables = file_config.github_variables.iter().map(|variable| variable.resolve()).collect::<Result<Vec<_>>>()?;
+    let topics = crate::config::github_config::combine_topics(
+        &category,
+        &template_metadata.topics,
+        &file_config.get_github_topics(),
+    )?;
     
     let project_name = file_config.project_name.clone();
     if project_name.is_empty() {
         return Err(Error::new(ErrorKind::InvalidData, "project_name is required in config file"));
     }
 
-    // Get organization from REPO_URL
-    let organization = extract_organization_from_repo_url()?;
-    println!("Using organization: {}", organization);
+    // Get organization from the config, or from REPO_URL
+    let organization = match &file_config.server.organization {
+        Some(organization) => organization.clone(),
+        None => extract_organization_from_repo_url()?,
+    };
+    if provider != ProviderKind::GitHub || file_config.server.is_self_hosted(provider) {
+        println!("Using organization: {} on {}", organization, file_config.server.url(provider));
+    } else {
+        println!("Using organization: {}", organization);
+    }
 
     // Ask for repository name with option to use project name
     let repo_name = prompt_for_repo_name(&project_name)
         .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "Repository name is required"))?;
 
+    // Get description from config or use default
+    let description = file_config.additional_vars
+        .get("description")
+        .cloned()
+        .unwrap_or_else(|| "Generated project".to_string());
+
+    // The repository default branch is the branch the initial commit is pushed to
+    let mut git_settings = file_config.git.or(&template_metadata.git);
+    if let Some(branch) = &file_config.repository.default_branch {
+        git_settings.default_branch = Some(branch.clone());
+    }
+
+    let mut settings = file_config.repository.clone();
+    if let Some(if_exists) = args.if_exists {
+        settings.if_exists = if_exists;
+    }
+    if args.delete_on_failure {
+        settings.delete_on_push_failure = true;
+    }
+
+    let plan = RepositoryPlan {
+        provider,
+        server: file_config.server.clone(),
+        organization,
+        name: repo_name,
+        description,
+        topics,
+        labels: file_config.labels.clone(),
+        settings,
+        branch_protection,
+        teams: file_config.github_teams.clone(),
+        collaborators: file_config.github_collaborators.clone(),
+        secrets,
+        variables,
+        git: git_settings,
+    };
+    plan.check_provider_support()?;
+
+    let token = args.token.or_else(|| std::env::var(provider.token_variable()).ok());
+
+    if args.dry_run {
+        plan.print();
+        // Without a token the dry run still works, it only cannot look at the existing repository
+        match &token {
+            Some(token) => println!("  Target:         {}", remote::resolve_target(token, &plan).await?),
+            None => println!("  Target:         not checked, no {} token", provider),
+        }
+        println!("\nDry run: nothing was generated or created");
+        return Ok(());
+    }
+
+    let token = remote::provider_token(provider, token)?;
+
+    // An existing repository is handled before generating anything
+    let target = remote::resolve_target(&token, &plan).await?;
+    println!("Target: {}", target);
+
     // Create temporary directory for remote mode
     let temp_dir = std::env::temp_dir().join(format!("project-generator-{}", project_name));
     let project_path = temp_dir;
 
-    // Generate the project in temp directory
+    // The temporary directory belongs to us, remove leftovers from a previous run
+    if provider::retry::has_pending_push(&project_path) {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!(
+                "'{}' holds a project whose push failed. Push it with `project-generator retry-push {}` or delete it first",
+                project_path.display(),
+                project_path.display()
+            ),
+        ));
+    }
+    if project_path.exists() {
+        utils::context::debug_print(&format!("Removing stale temporary directory: {}", project_path.display()));
+        std::fs::remove_dir_all(&project_path)?;
+    }
+
+    // Generate the project in temp directory, dependencies are installed before Git operations
     if args.config.is_some() {
-        crate::generate::handle_config_mode_with_path(&template_path, &project_name, &project_path, false)
-            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+        crate::generate::handle_config_mode_with_path(&template_path, &project_name, &project_path, true)
+            .map_err(|e| Error::other(e.to_string()))?;
     } else {
         handle_interactive_mode(&template_path)
-            .map_err(|e| Error::new(ErrorKind::Other, e.to_string()))?;
+            .map_err(|e| Error::other(e.to_string()))?;
     }
 
-    // Install dependencies AFTER copying template files but BEFORE Git operations
-    crate::generate::project_generator::install_dependencies(&project_path)
-        .map_err(|e| Error::new(ErrorKind::Other, format!("Failed to install dependencies: {}", e)))?;
+    remote::write_codeowners(&plan, &project_path)?;
 
-    // Get description from config or use default
-    let description = file_config.additional_vars
-        .get("description")
-        .cloned()
-        .unwrap_or_else(|| "Generated project".to_string());
-
-    // Create GitHub repository and push the code (includes full Git workflow)
-    let github_tag = file_config.get_github_tag().map(|s| s.as_str());
-    let result = create_github_repository_with_code(&token, &repo_name, &project_path, &description, github_tag).await;
+    // Create the repository and push the code (includes full Git workflow)
+    let result = create_repository_with_code(&token, &plan, &target, &project_path).await;
 
-    // Clean up temporary directory
-    if let Err(e) = std::fs::remove_dir_all(&project_path) {
-        eprintln!("Warning: Failed to clean up temporary directory '{}': {}", project_path.display(), e);
-    } else {
-        println!("Temporary directory cleaned up successfully");
+    // Clean up temporary directory, unless it is kept for retry-push
+    let kept_for_retry = result.is_err() && provider::retry::has_pending_push(&project_path);
+    if !kept_for_retry {
+        if let Err(e) = std::fs::remove_dir_all(&project_path) {
+            eprintln!("Warning: Failed to clean up temporary directory '{}': {}", project_path.display(), e);
+        } else {
+            println!("Temporary directory cleaned up successfully");
+        }
     }
 
     result?;
     Ok(())
 }
+
@@ -0,0 +1,198 @@
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION, RETRY_AFTER, USER_AGENT};
+use reqwest::{Method, RequestBuilder, Response, StatusCode};
+
+use crate::config::{
+    API_MAX_RETRIES, API_MAX_RETRY_WAIT_SECS, API_REQUEST_TIMEOUT_SECS, API_RETRY_BASE_DELAY_MS,
+};
+use crate::provider::error::{ApiError, ApiErrorKind};
+use crate::utils::context;
+
+/// Connection pool shared by every API call of the run.
+static HTTP: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// Authenticated hosting provider API client retrying transient failures.
+///
+/// Network errors, server errors and rate limits are retried with exponential backoff,
+/// waiting as long as `Retry-After` or the rate limit reset header ask when they are given.
+/// POST requests are only retried when they could not reach the API, as they may not be
+/// idempotent.
+pub struct ApiClient {
+    http: reqwest::Client,
+    token: String,
+    /// Provider name used in error messages.
+    service: &'static str,
+    accept: &'static str,
+}
+
+impl ApiClient {
+    pub fn new(service: &'static str, token: &str, accept: &'static str) -> Self {
+        let http = HTTP.get_or_init(|| {
+            reqwest::Client::builder()
+                .timeout(Duration::from_secs(API_REQUEST_TIMEOUT_SECS))
+                .build()
+                .unwrap_or_default()
+        });
+        Self {
+            http: http.clone(),
+            token: token.to_string(),
+            service,
+            accept,
+        }
+    }
+
+    pub fn get(&self, url: String) -> RequestBuilder {
+        self.http.request(Method::GET, url)
+    }
+
+    pub fn post(&self, url: String) -> RequestBuilder {
+        self.http.request(Method::POST, url)
+    }
+
+    pub fn put(&self, url: String) -> RequestBuilder {
+        self.http.request(Method::PUT, url)
+    }
+
+    pub fn patch(&self, url: String) -> RequestBuilder {
+        self.http.request(Method::PATCH, url)
+    }
+
+    pub fn delete(&self, url: String) -> RequestBuilder {
+        self.http.request(Method::DELETE, url)
+    }
+
+    /// Sends a request, turning error statuses into `ApiErrorKind::Status`.
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, ApiError> {
+        let response = self.execute(request).await?;
+        if !response.status().is_success() {
+            return Err(ApiError::from_response(self.service, response).await);
+        }
+        Ok(response)
+    }
+
+    /// Fetches a resource that may not exist, None on 404.
+    pub async fn get_optional(&self, url: String) -> Result<Option<Response>, ApiError> {
+        let response = self.execute(self.get(url)).await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(ApiError::from_response(self.service, response).await);
+        }
+        Ok(Some(response))
+    }
+
+    /// Sends a request with retries and returns the last response, whatever its status.
+    pub async fn execute(&self, request: RequestBuilder) -> Result<Response, ApiError> {
+        let request = request.headers(self.headers()?).build().map_err(|e| self.error(ApiErrorKind::Network(e)))?;
+        let idempotent = request.method() != Method::POST;
+
+        let mut attempt = 0;
+        loop {
+            // Bodies are buffered JSON, so the request can always be cloned
+            let Some(attempt_request) = request.try_clone() else {
+                return self.http.execute(request).await.map_err(|e| self.error(ApiErrorKind::Network(e)));
+            };
+            let can_retry = attempt < API_MAX_RETRIES;
+
+            let (delay, reason) = match self.http.execute(attempt_request).await {
+                Ok(response) => match retry_delay(&response, attempt, idempotent).map_err(|kind| self.error(kind))? {
+                    Some(retry) if can_retry => retry,
+                    _ => return Ok(response),
+                },
+                // A connection failure means the request never reached the API
+                Err(e) if can_retry && (e.is_connect() || (idempotent && e.is_timeout())) => {
+                    (backoff(attempt), e.to_string())
+                }
+                Err(e) => return Err(self.error(ApiErrorKind::Network(e))),
+            };
+
+            eprintln!(
+                "Warning: {} {}: {}, retrying in {}s ({}/{})",
+                request.method(),
+                request.url().path(),
+                reason,
+                delay.as_secs_f32().ceil(),
+                attempt + 1,
+                API_MAX_RETRIES
+            );
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    fn error(&self, kind: ApiErrorKind) -> ApiError {
+        ApiError::new(self.service, kind)
+    }
+
+    fn headers(&self) -> Result<HeaderMap, ApiError> {
+        let mut authorization = HeaderValue::from_str(&format!("Bearer {}", self.token))
+            .map_err(|_| self.error(ApiErrorKind::InvalidToken))?;
+        authorization.set_sensitive(true);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, authorization);
+        headers.insert(ACCEPT, HeaderValue::from_static(self.accept));
+        headers.insert(USER_AGENT, HeaderValue::from_static("NextNode-Project-Generator/1.0"));
+        Ok(headers)
+    }
+}
+
+/// Percent-encodes a value used as one URL path segment, e.g. a branch named `release/1.x`.
+pub fn encode_path_segment(segment: &str) -> String {
+    segment
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Delay before retrying a response and why, None when it must not be retried.
+fn retry_delay(response: &Response, attempt: u32, idempotent: bool) -> Result<Option<(Duration, String)>, ApiErrorKind> {
+    let status = response.status();
+    let headers = response.headers();
+    // GitHub prefixes the rate limit headers with X-, GitLab does not
+    let rate_limit_header =
+        |name: &str| header_secs(headers, &format!("x-{}", name)).or_else(|| header_secs(headers, name));
+    let remaining = rate_limit_header("ratelimit-remaining");
+    let retry_after = header_secs(headers, RETRY_AFTER.as_str());
+
+    // Primary limits exhaust X-RateLimit-Remaining, secondary limits send Retry-After
+    let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::FORBIDDEN && (remaining == Some(0) || retry_after.is_some()));
+    if rate_limited {
+        let wait = match (retry_after, remaining, rate_limit_header("ratelimit-reset")) {
+            (Some(secs), _, _) => Duration::from_secs(secs),
+            (None, Some(0), Some(reset)) => Duration::from_secs(reset.saturating_sub(unix_now()) + 1),
+            _ => backoff(attempt),
+        };
+        context::debug_print(&format!("Rate limited ({}), reset in {}s", status, wait.as_secs()));
+        if wait.as_secs() > API_MAX_RETRY_WAIT_SECS {
+            return Err(ApiErrorKind::RateLimited { reset_in_secs: wait.as_secs() });
+        }
+        return Ok(Some((wait, "rate limit exceeded".to_string())));
+    }
+
+    if status.is_server_error() && idempotent {
+        return Ok(Some((backoff(attempt), format!("server error {}", status))));
+    }
+    Ok(None)
+}
+
+/// Exponential backoff: the base delay doubled on every attempt.
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_millis(API_RETRY_BASE_DELAY_MS.saturating_mul(1 << attempt.min(16)))
+}
+
+/// Integer header such as `Retry-After` in seconds or `X-RateLimit-Reset` as a timestamp.
+fn header_secs(headers: &HeaderMap, name: &str) -> Option<u64> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
@@ -0,0 +1,145 @@
+use std::fmt;
+
+use reqwest::{Response, StatusCode};
+
+/// Failure of a hosting provider API call, `service` naming the provider in messages.
+#[derive(Debug)]
+pub struct ApiError {
+    pub service: &'static str,
+    pub kind: ApiErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ApiErrorKind {
+    /// The API answered with an error status.
+    Status {
+        status: StatusCode,
+        /// The provider's `message`, or the raw body when it is not an error document.
+        message: String,
+        /// Details of validation failures, from the `errors` array.
+        errors: Vec<String>,
+        documentation_url: Option<String>,
+    },
+    /// The request could not be sent or its response could not be read.
+    Network(reqwest::Error),
+    /// The rate limit is exhausted for longer than we are willing to wait.
+    RateLimited { reset_in_secs: u64 },
+    /// The token cannot be sent in an HTTP header.
+    InvalidToken,
+}
+
+impl ApiError {
+    pub fn new(service: &'static str, kind: ApiErrorKind) -> Self {
+        Self { service, kind }
+    }
+
+    /// Reads the error document of a failed response.
+    ///
+    /// GitHub and Gitea send a `message` string, GitLab sends either a string or an object
+    /// of field errors in `message`, or an `error` string for authentication failures.
+    pub async fn from_response(service: &'static str, response: Response) -> Self {
+        let status = response.status();
+        let body = match response.text().await {
+            Ok(body) => body,
+            Err(e) => return Self::new(service, ApiErrorKind::Network(e)),
+        };
+
+        let Ok(document) = serde_json::from_str::<serde_json::Value>(&body) else {
+            return Self::new(
+                service,
+                ApiErrorKind::Status {
+                    status,
+                    message: body.trim().to_string(),
+                    errors: Vec::new(),
+                    documentation_url: None,
+                },
+            );
+        };
+        let mut errors: Vec<String> = document["errors"]
+            .as_array()
+            .map(|errors| errors.iter().filter_map(describe_validation_error).collect())
+            .unwrap_or_default();
+        let message = match &document["message"] {
+            serde_json::Value::String(message) => message.clone(),
+            serde_json::Value::Object(fields) => {
+                errors.extend(fields.iter().map(|(field, problems)| describe_field_errors(field, problems)));
+                "Validation failed".to_string()
+            }
+            _ => document["error"].as_str().unwrap_or(body.trim()).to_string(),
+        };
+        Self::new(
+            service,
+            ApiErrorKind::Status {
+                status,
+                message,
+                errors,
+                documentation_url: document["documentation_url"]
+                    .as_str()
+                    .or_else(|| document["url"].as_str())
+                    .map(str::to_string),
+            },
+        )
+    }
+}
+
+/// Entries of `errors` are either plain strings or objects with a `message` or a `code`.
+fn describe_validation_error(error: &serde_json::Value) -> Option<String> {
+    if let Some(message) = error.as_str().or_else(|| error["message"].as_str()) {
+        return Some(message.to_string());
+    }
+    let code = error["code"].as_str()?;
+    match error["field"].as_str() {
+        Some(field) => Some(format!("{} {}", field, code.replace('_', " "))),
+        None => Some(code.replace('_', " ")),
+    }
+}
+
+/// GitLab field errors, e.g. `"name": ["has already been taken"]`.
+fn describe_field_errors(field: &str, problems: &serde_json::Value) -> String {
+    let problems = match problems {
+        serde_json::Value::Array(problems) => problems
+            .iter()
+            .map(|problem| problem.as_str().map(str::to_string).unwrap_or_else(|| problem.to_string()))
+            .collect::<Vec<_>>()
+            .join(", "),
+        serde_json::Value::String(problem) => problem.clone(),
+        other => other.to_string(),
+    };
+    format!("{} {}", field, problems)
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ApiErrorKind::Status { status, message, errors, documentation_url } => {
+                write!(f, "{} API error ({}): {}", self.service, status, message)?;
+                if !errors.is_empty() {
+                    write!(f, " ({})", errors.join("; "))?;
+                }
+                if let Some(url) = documentation_url {
+                    write!(f, ", see {}", url)?;
+                }
+                Ok(())
+            }
+            ApiErrorKind::Network(e) => write!(f, "Failed to reach the {} API: {}", self.service, e),
+            ApiErrorKind::RateLimited { reset_in_secs } => write!(
+                f,
+                "{} API rate limit exceeded, it resets in {} minute(s)",
+                self.service,
+                reset_in_secs.div_ceil(60)
+            ),
+            ApiErrorKind::InvalidToken => {
+                write!(f, "The {} token contains characters not allowed in an HTTP header", self.service)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.kind {
+            ApiErrorKind::Network(e) => Some(e),
+            _ => None,
+        }
+    }
+}
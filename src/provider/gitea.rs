@@ -0,0 +1,182 @@
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+
+use crate::config::github_config::{RepositorySettings, Visibility};
+use crate::config::provider_config::{LabelDefinition, ProviderKind, ServerSettings};
+use crate::provider::client::ApiClient;
+use crate::provider::{ExistingRepository, Provider, ProviderResult};
+
+const SERVICE: &str = "Gitea";
+
+/// Gitea and Forgejo repositories, created in the organization.
+pub struct GiteaRepo {
+    client: ApiClient,
+    token: String,
+    api_url: String,
+    url: String,
+    organization: String,
+}
+
+impl GiteaRepo {
+    pub fn new(token: &str, server: &ServerSettings, organization: &str) -> Self {
+        Self {
+            client: ApiClient::new(SERVICE, token, "application/json"),
+            token: token.to_string(),
+            api_url: server.api_url(ProviderKind::Gitea),
+            url: server.url(ProviderKind::Gitea).to_string(),
+            organization: organization.to_string(),
+        }
+    }
+
+    /// API URL of a repository resource, `path` empty or starting with a slash.
+    fn repo_endpoint(&self, name: &str, path: &str) -> String {
+        format!("{}/repos/{}/{}{}", self.api_url, self.organization, name, path)
+    }
+
+    /// Ids of the labels of the repository by name. Gitea addresses labels by id and does not
+    /// keep their names unique, so they are listed rather than looked up.
+    async fn label_ids(&self, name: &str) -> ProviderResult<Vec<(String, u64)>> {
+        let mut ids = Vec::new();
+        for page in 1.. {
+            let request = self.client.get(self.repo_endpoint(name, &format!("/labels?limit=50&page={}", page)));
+            let labels: Vec<Value> = self.client.send(request).await?.json().await
+                .map_err(|e| format!("Failed to parse labels response: {}", e))?;
+            if labels.is_empty() {
+                break;
+            }
+            ids.extend(labels.iter().filter_map(|label| Some((label["name"].as_str()?.to_string(), label["id"].as_u64()?))));
+        }
+        Ok(ids)
+    }
+
+    async fn edit_repository(&self, name: &str, fields: &Map<String, Value>) -> ProviderResult<()> {
+        let request = self.client.patch(self.repo_endpoint(name, "")).json(fields);
+        self.client.send(request).await?;
+        Ok(())
+    }
+}
+
+/// Repository toggles in Gitea's field names, only settable by editing the repository.
+fn toggle_fields(settings: &RepositorySettings) -> Map<String, Value> {
+    [
+        ("allow_merge_commits", settings.allow_merge_commit),
+        ("allow_squash_merge", settings.allow_squash_merge),
+        ("allow_rebase", settings.allow_rebase_merge),
+        ("default_delete_branch_after_merge", settings.delete_branch_on_merge),
+        ("has_issues", settings.has_issues),
+        ("has_wiki", settings.has_wiki),
+        ("has_projects", settings.has_projects),
+    ]
+    .into_iter()
+    .filter_map(|(name, value)| value.map(|value| (name.to_string(), json!(value))))
+    .collect()
+}
+
+#[async_trait]
+impl Provider for GiteaRepo {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Gitea
+    }
+
+    fn token(&self) -> &str {
+        &self.token
+    }
+
+    fn remote_url(&self, name: &str) -> String {
+        format!("{}/{}/{}.git", self.url, self.organization, name)
+    }
+
+    async fn get_repository(&self, name: &str) -> ProviderResult<Option<ExistingRepository>> {
+        let Some(response) = self.client.get_optional(self.repo_endpoint(name, "")).await? else {
+            return Ok(None);
+        };
+        let repo_data: Value = response.json().await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        Ok(Some(ExistingRepository {
+            web_url: repo_data["html_url"].as_str().ok_or("No html_url in response")?.to_string(),
+            default_branch: repo_data["default_branch"].as_str().unwrap_or("main").to_string(),
+            empty: repo_data["empty"].as_bool().unwrap_or(false),
+        }))
+    }
+
+    async fn create_repository(&self, name: &str, description: &str, settings: &RepositorySettings)
+        -> ProviderResult<String> {
+        let request = self
+            .client
+            .post(format!("{}/orgs/{}/repos", self.api_url, self.organization))
+            .json(&json!({
+                "name": name,
+                "description": description,
+                "private": settings.visibility == Visibility::Private,
+                "auto_init": false,
+            }));
+        let repo_data: Value = self.client.send(request).await?.json().await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        let repo_url = repo_data["html_url"].as_str().ok_or("No html_url in response")?.to_string();
+
+        let toggles = toggle_fields(settings);
+        if !toggles.is_empty() {
+            self.edit_repository(name, &toggles).await?;
+        }
+        Ok(repo_url)
+    }
+
+    async fn update_settings(&self, name: &str, description: &str, settings: &RepositorySettings)
+        -> ProviderResult<()> {
+        let mut fields = toggle_fields(settings);
+        fields.insert("description".to_string(), json!(description));
+        fields.insert("private".to_string(), json!(settings.visibility == Visibility::Private));
+        self.edit_repository(name, &fields).await
+    }
+
+    async fn delete_repository(&self, name: &str) -> ProviderResult<()> {
+        let request = self.client.delete(self.repo_endpoint(name, ""));
+        self.client.send(request).await?;
+        Ok(())
+    }
+
+    async fn set_topics(&self, name: &str, topics: &[String]) -> ProviderResult<()> {
+        let request = self
+            .client
+            .put(self.repo_endpoint(name, "/topics"))
+            .json(&json!({ "topics": topics }));
+        self.client.send(request).await?;
+        Ok(())
+    }
+
+    async fn set_labels(&self, name: &str, labels: &[LabelDefinition]) -> ProviderResult<()> {
+        let existing = self.label_ids(name).await?;
+        for label in labels {
+            let color = format!("#{}", label.hex_color());
+            let request = match existing.iter().find(|(existing, _)| existing.eq_ignore_ascii_case(&label.name)) {
+                Some((_, id)) => self
+                    .client
+                    .patch(self.repo_endpoint(name, &format!("/labels/{}", id)))
+                    .json(&json!({ "color": color, "description": label.description() })),
+                None => self
+                    .client
+                    .post(self.repo_endpoint(name, "/labels"))
+                    .json(&json!({ "name": label.name, "color": color, "description": label.description() })),
+            };
+            self.client.send(request).await?;
+        }
+        Ok(())
+    }
+
+    async fn update_default_branch(&self, name: &str, branch: &str) -> ProviderResult<()> {
+        let mut fields = Map::new();
+        fields.insert("default_branch".to_string(), json!(branch));
+        self.edit_repository(name, &fields).await
+    }
+
+    async fn open_pull_request(&self, name: &str, head: &str, base: &str, title: &str, body: &str)
+        -> ProviderResult<String> {
+        let request = self
+            .client
+            .post(self.repo_endpoint(name, "/pulls"))
+            .json(&json!({ "title": title, "head": head, "base": base, "body": body }));
+        let pull_request: Value = self.client.send(request).await?.json().await
+            .map_err(|e| format!("Failed to parse pull request response: {}", e))?;
+        Ok(pull_request["html_url"].as_str().ok_or("No html_url in pull request response")?.to_string())
+    }
+}
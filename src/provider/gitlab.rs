@@ -0,0 +1,174 @@
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+
+use crate::config::github_config::RepositorySettings;
+use crate::config::provider_config::{LabelDefinition, ProviderKind, ServerSettings};
+use crate::provider::client::{encode_path_segment, ApiClient};
+use crate::provider::{ExistingRepository, Provider, ProviderResult};
+
+const SERVICE: &str = "GitLab";
+
+/// GitLab projects, created in the group given as organization (subgroups as `group/sub`).
+pub struct GitLabRepo {
+    client: ApiClient,
+    token: String,
+    api_url: String,
+    url: String,
+    group: String,
+}
+
+impl GitLabRepo {
+    pub fn new(token: &str, server: &ServerSettings, organization: &str) -> Self {
+        Self {
+            client: ApiClient::new(SERVICE, token, "application/json"),
+            token: token.to_string(),
+            api_url: server.api_url(ProviderKind::GitLab),
+            url: server.url(ProviderKind::GitLab).to_string(),
+            group: organization.to_string(),
+        }
+    }
+
+    /// API URL of a project resource, the project being identified by its encoded full path.
+    fn project_endpoint(&self, name: &str, path: &str) -> String {
+        format!("{}/projects/{}{}", self.api_url, encode_path(&format!("{}/{}", self.group, name)), path)
+    }
+
+    async fn update_project(&self, name: &str, fields: &Map<String, Value>) -> ProviderResult<()> {
+        let request = self.client.put(self.project_endpoint(name, "")).json(fields);
+        self.client.send(request).await?;
+        Ok(())
+    }
+}
+
+/// Project fields matching the repository settings. GitLab has no project boards toggle and
+/// expresses the merge strategies as a merge method and a squash option.
+fn project_fields(description: &str, settings: &RepositorySettings) -> Map<String, Value> {
+    let mut fields = Map::new();
+    fields.insert("description".to_string(), json!(description));
+    fields.insert("visibility".to_string(), json!(settings.visibility.to_string()));
+    if let Some(enabled) = settings.has_issues {
+        fields.insert("issues_enabled".to_string(), json!(enabled));
+    }
+    if let Some(enabled) = settings.has_wiki {
+        fields.insert("wiki_enabled".to_string(), json!(enabled));
+    }
+    if let Some(delete) = settings.delete_branch_on_merge {
+        fields.insert("remove_source_branch_after_merge".to_string(), json!(delete));
+    }
+    if settings.allow_merge_commit == Some(false) {
+        fields.insert("merge_method".to_string(), json!("ff"));
+    }
+    if settings.allow_squash_merge == Some(false) {
+        fields.insert("squash_option".to_string(), json!("never"));
+    }
+    fields
+}
+
+/// Percent-encodes the slashes of a namespace path, as GitLab expects in a URL segment.
+fn encode_path(path: &str) -> String {
+    path.replace('/', "%2F")
+}
+
+#[async_trait]
+impl Provider for GitLabRepo {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::GitLab
+    }
+
+    fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// GitLab accepts access tokens over HTTPS with the `oauth2` username.
+    fn git_username(&self) -> &'static str {
+        "oauth2"
+    }
+
+    fn remote_url(&self, name: &str) -> String {
+        format!("{}/{}/{}.git", self.url, self.group, name)
+    }
+
+    async fn get_repository(&self, name: &str) -> ProviderResult<Option<ExistingRepository>> {
+        let Some(response) = self.client.get_optional(self.project_endpoint(name, "")).await? else {
+            return Ok(None);
+        };
+        let project: Value = response.json().await
+            .map_err(|e| format!("Failed to parse project response: {}", e))?;
+        Ok(Some(ExistingRepository {
+            web_url: project["web_url"].as_str().ok_or("No web_url in project response")?.to_string(),
+            default_branch: project["default_branch"].as_str().unwrap_or("main").to_string(),
+            empty: project["empty_repo"].as_bool().unwrap_or(false),
+        }))
+    }
+
+    async fn create_repository(&self, name: &str, description: &str, settings: &RepositorySettings)
+        -> ProviderResult<String> {
+        let request = self.client.get(format!("{}/namespaces/{}", self.api_url, encode_path(&self.group)));
+        let namespace: Value = self.client.send(request).await?.json().await
+            .map_err(|e| format!("Failed to parse namespace response: {}", e))?;
+        let namespace_id = namespace["id"].as_u64().ok_or("No id in namespace response")?;
+
+        let mut body = project_fields(description, settings);
+        body.insert("name".to_string(), json!(name));
+        body.insert("path".to_string(), json!(name));
+        body.insert("namespace_id".to_string(), json!(namespace_id));
+        body.insert("initialize_with_readme".to_string(), json!(false));
+
+        let request = self.client.post(format!("{}/projects", self.api_url)).json(&body);
+        let project: Value = self.client.send(request).await?.json().await
+            .map_err(|e| format!("Failed to parse project response: {}", e))?;
+        Ok(project["web_url"].as_str().ok_or("No web_url in project response")?.to_string())
+    }
+
+    async fn update_settings(&self, name: &str, description: &str, settings: &RepositorySettings)
+        -> ProviderResult<()> {
+        self.update_project(name, &project_fields(description, settings)).await
+    }
+
+    /// Projects are deleted asynchronously, or marked for deletion when the group delays it.
+    async fn delete_repository(&self, name: &str) -> ProviderResult<()> {
+        let request = self.client.delete(self.project_endpoint(name, ""));
+        self.client.send(request).await?;
+        Ok(())
+    }
+
+    async fn set_topics(&self, name: &str, topics: &[String]) -> ProviderResult<()> {
+        let mut fields = Map::new();
+        fields.insert("topics".to_string(), json!(topics));
+        self.update_project(name, &fields).await
+    }
+
+    /// Project labels, GitLab takes the color with its `#`.
+    async fn set_labels(&self, name: &str, labels: &[LabelDefinition]) -> ProviderResult<()> {
+        for label in labels {
+            let endpoint = self.project_endpoint(name, &format!("/labels/{}", encode_path_segment(&label.name)));
+            let color = format!("#{}", label.hex_color());
+            let request = match self.client.get_optional(endpoint.clone()).await? {
+                Some(_) => self.client.put(endpoint).json(&json!({ "color": color, "description": label.description() })),
+                None => self
+                    .client
+                    .post(self.project_endpoint(name, "/labels"))
+                    .json(&json!({ "name": label.name, "color": color, "description": label.description() })),
+            };
+            self.client.send(request).await?;
+        }
+        Ok(())
+    }
+
+    async fn update_default_branch(&self, name: &str, branch: &str) -> ProviderResult<()> {
+        let mut fields = Map::new();
+        fields.insert("default_branch".to_string(), json!(branch));
+        self.update_project(name, &fields).await
+    }
+
+    async fn open_pull_request(&self, name: &str, head: &str, base: &str, title: &str, body: &str)
+        -> ProviderResult<String> {
+        let request = self
+            .client
+            .post(self.project_endpoint(name, "/merge_requests"))
+            .json(&json!({ "source_branch": head, "target_branch": base, "title": title, "description": body }));
+        let merge_request: Value = self.client.send(request).await?.json().await
+            .map_err(|e| format!("Failed to parse merge request response: {}", e))?;
+        Ok(merge_request["web_url"].as_str().ok_or("No web_url in merge request response")?.to_string())
+    }
+}
@@ -0,0 +1,187 @@
+pub mod client;
+pub mod error;
+pub mod gitea;
+pub mod gitlab;
+pub mod plan;
+pub mod remote;
+pub mod retry;
+
+use std::path::Path;
+
+use async_trait::async_trait;
+use git2::{Cred, RemoteCallbacks};
+
+use crate::config::github_config::{BranchProtection, Permission, RepositorySettings};
+use crate::config::provider_config::{LabelDefinition, ProviderKind, ServerSettings};
+use crate::config::GitSettings;
+use crate::git;
+use crate::github::repo::GitHubRepo;
+
+pub type ProviderResult<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+/// A repository that already exists in the organization.
+pub struct ExistingRepository {
+    pub web_url: String,
+    pub default_branch: String,
+    /// The repository has no commits yet.
+    pub empty: bool,
+}
+
+/// Hosting service remote mode creates repositories on.
+///
+/// Repositories are named relative to the configured organization (a group on GitLab).
+/// Branch protection, access and Actions settings only exist on GitHub, the other providers
+/// reject them and remote mode refuses such plans before generating.
+#[async_trait]
+pub trait Provider: Send + Sync {
+    fn kind(&self) -> ProviderKind;
+
+    fn token(&self) -> &str;
+
+    /// Username sent with the token for git operations.
+    fn git_username(&self) -> &'static str {
+        "git"
+    }
+
+    /// URL the code of a repository is pushed to.
+    fn remote_url(&self, name: &str) -> String;
+
+    /// Looks up a repository, None when it does not exist.
+    async fn get_repository(&self, name: &str) -> ProviderResult<Option<ExistingRepository>>;
+
+    /// Creates an empty repository and returns its web URL.
+    async fn create_repository(&self, name: &str, description: &str, settings: &RepositorySettings)
+        -> ProviderResult<String>;
+
+    /// Applies the description, visibility and settings to an existing repository.
+    async fn update_settings(&self, name: &str, description: &str, settings: &RepositorySettings)
+        -> ProviderResult<()>;
+
+    async fn delete_repository(&self, name: &str) -> ProviderResult<()>;
+
+    /// Replaces the topics of the repository.
+    async fn set_topics(&self, name: &str, topics: &[String]) -> ProviderResult<()>;
+
+    /// Creates the labels, or updates the color and description of those that already exist.
+    async fn set_labels(&self, name: &str, labels: &[LabelDefinition]) -> ProviderResult<()>;
+
+    /// Sets the default branch, which must already have been pushed.
+    async fn update_default_branch(&self, name: &str, branch: &str) -> ProviderResult<()>;
+
+    /// Opens a pull request (merge request on GitLab) from `head` into `base` and returns its URL.
+    async fn open_pull_request(&self, name: &str, head: &str, base: &str, title: &str, body: &str)
+        -> ProviderResult<String>;
+
+    async fn protect_branch(&self, _name: &str, _branch: &str, _protection: &BranchProtection) -> ProviderResult<()> {
+        Err(unsupported(self.kind(), "branch protection"))
+    }
+
+    async fn add_team(&self, _name: &str, _slug: &str, _permission: Permission) -> ProviderResult<()> {
+        Err(unsupported(self.kind(), "team access"))
+    }
+
+    /// Returns true when an invitation was sent, false when the user already had access.
+    async fn add_collaborator(&self, _name: &str, _username: &str, _permission: Permission) -> ProviderResult<bool> {
+        Err(unsupported(self.kind(), "collaborators"))
+    }
+
+    async fn set_secret(&self, _name: &str, _secret_name: &str, _value: &str) -> ProviderResult<()> {
+        Err(unsupported(self.kind(), "Actions secrets"))
+    }
+
+    async fn create_variable(&self, _name: &str, _variable_name: &str, _value: &str) -> ProviderResult<()> {
+        Err(unsupported(self.kind(), "Actions variables"))
+    }
+}
+
+fn unsupported(kind: ProviderKind, feature: &str) -> Box<dyn std::error::Error + Send + Sync> {
+    format!("{} is not supported on {}", feature, kind).into()
+}
+
+/// Client of the configured provider and instance.
+pub fn connect(kind: ProviderKind, server: &ServerSettings, organization: &str, token: &str) -> Box<dyn Provider> {
+    match kind {
+        ProviderKind::GitHub => Box::new(GitHubRepo::new(token, server, organization)),
+        ProviderKind::GitLab => Box::new(gitlab::GitLabRepo::new(token, server, organization)),
+        ProviderKind::Gitea => Box::new(gitea::GiteaRepo::new(token, server, organization)),
+    }
+}
+
+/// Initializes the repository with the first commit of the generated code and pushes it to
+/// the default branch of `repo_url`.
+pub fn initialize_git_and_push(
+    provider: &dyn Provider,
+    local_path: &Path,
+    repo_url: &str,
+    git_settings: &GitSettings,
+) -> ProviderResult<()> {
+    // Remove existing .git directory if it exists
+    let git_dir = local_path.join(".git");
+    if git_dir.exists() {
+        std::fs::remove_dir_all(&git_dir)?;
+    }
+
+    // git init, git add . and the first commit (dependencies are already installed)
+    let repo = git::init_repository(local_path, git_settings)?;
+
+    // git remote add origin <url>
+    let mut remote = repo.remote("origin", repo_url)?;
+
+    // git push -u origin <branch>
+    let mut push_options = git2::PushOptions::new();
+    push_options.remote_callbacks(callbacks(provider));
+    let branch = git_settings.default_branch();
+    remote.push(&[format!("HEAD:refs/heads/{}", branch)], Some(&mut push_options))?;
+
+    Ok(())
+}
+
+/// Commits the generated code on top of `base` of the existing repository and pushes it to
+/// the new branch `branch`, leaving `base` untouched.
+pub fn push_to_new_branch(
+    provider: &dyn Provider,
+    local_path: &Path,
+    repo_url: &str,
+    base: &str,
+    branch: &str,
+    git_settings: &GitSettings,
+) -> ProviderResult<()> {
+    let git_dir = local_path.join(".git");
+    if git_dir.exists() {
+        std::fs::remove_dir_all(&git_dir)?;
+    }
+
+    let repo = git::init_empty(local_path, branch)?;
+    let mut remote = repo.remote("origin", repo_url)?;
+
+    // git fetch origin <base>
+    let mut fetch_options = git2::FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks(provider));
+    remote.fetch(
+        &[format!("refs/heads/{0}:refs/remotes/origin/{0}", base)],
+        Some(&mut fetch_options),
+        None,
+    )?;
+    let parent = repo
+        .find_reference(&format!("refs/remotes/origin/{}", base))?
+        .peel_to_commit()?;
+
+    git::commit_all(&repo, git_settings, Some(&parent))?;
+
+    let mut push_options = git2::PushOptions::new();
+    push_options.remote_callbacks(callbacks(provider));
+    remote.push(&[format!("HEAD:refs/heads/{}", branch)], Some(&mut push_options))?;
+
+    Ok(())
+}
+
+/// Authenticates git operations with the token.
+fn callbacks(provider: &dyn Provider) -> RemoteCallbacks<'static> {
+    let mut callbacks = RemoteCallbacks::new();
+    let username = provider.git_username();
+    let token = provider.token().to_string();
+    callbacks.credentials(move |_url, username_from_url, _allowed_types| {
+        Cred::userpass_plaintext(username_from_url.unwrap_or(username), &token)
+    });
+    callbacks
+}
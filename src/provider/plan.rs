@@ -0,0 +1,156 @@
+use std::io::{Error, ErrorKind, Result};
+
+use crate::config::github_config::{
+    self, BranchProtection, CollaboratorAccess, RepositorySettings, ResolvedValue, TeamAccess, Visibility,
+};
+use crate::config::provider_config::{LabelDefinition, ProviderKind, ServerSettings};
+use crate::config::GitSettings;
+
+/// What remote mode creates on the hosting provider, resolved from the configuration file and the
+/// template before anything is generated, so `--dry-run` can print it.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RepositoryPlan {
+    /// Absent from push states saved before providers were configurable.
+    #[serde(default)]
+    pub provider: ProviderKind,
+    pub server: ServerSettings,
+    pub organization: String,
+    pub name: String,
+    pub description: String,
+    pub topics: Vec<String>,
+    /// Absent from push states saved before labels were configurable.
+    #[serde(default)]
+    pub labels: Vec<LabelDefinition>,
+    pub settings: RepositorySettings,
+    pub branch_protection: Option<BranchProtection>,
+    pub teams: Vec<TeamAccess>,
+    pub collaborators: Vec<CollaboratorAccess>,
+    /// Actions secrets, their values are never printed nor saved.
+    #[serde(skip)]
+    pub secrets: Vec<ResolvedValue>,
+    #[serde(skip)]
+    pub variables: Vec<ResolvedValue>,
+    /// Settings of the pushed commit, its branch is the repository default branch.
+    pub git: GitSettings,
+}
+
+impl RepositoryPlan {
+    pub fn print(&self) {
+        println!("\nRepository plan:");
+        println!("  Repository:     {}/{}", self.organization, self.name);
+        if self.provider != ProviderKind::GitHub || self.server.is_self_hosted(self.provider) {
+            println!(
+                "  Server:         {} {} (API {})",
+                self.provider,
+                self.server.url(self.provider),
+                self.server.api_url(self.provider)
+            );
+        }
+        println!("  Description:    {}", self.description);
+        println!("  Visibility:     {}", self.settings.visibility);
+        println!("  If it exists:   {}", self.settings.if_exists);
+        println!("  Default branch: {}", self.git.default_branch());
+        println!(
+            "  Initial commit: \"{}\" by {} <{}>",
+            self.git.commit_message(),
+            self.git.author_name(),
+            self.git.author_email()
+        );
+        if !self.topics.is_empty() {
+            println!("  Topics:         {}", self.topics.join(", "));
+        }
+        if !self.labels.is_empty() {
+            println!("  Labels:");
+            for label in &self.labels {
+                match &label.description {
+                    Some(description) => println!("    {} (#{}): {}", label.name, label.hex_color(), description),
+                    None => println!("    {} (#{})", label.name, label.hex_color()),
+                }
+            }
+        }
+
+        let toggles = self.settings.toggles();
+        if toggles.is_empty() {
+            println!("  Settings:       organization defaults");
+        } else {
+            println!("  Settings:");
+            for (name, value) in toggles {
+                println!("    {}: {}", name, value);
+            }
+        }
+
+        if let Some(protection) = &self.branch_protection {
+            println!("  Branch protection on '{}':", self.protected_branch());
+            for rule in protection.describe() {
+                println!("    {}", rule);
+            }
+        }
+
+        if !self.teams.is_empty() || !self.collaborators.is_empty() {
+            println!("  Access:");
+            for team in &self.teams {
+                println!("    team {}/{}: {}", self.organization, team.slug, team.permission);
+            }
+            for user in &self.collaborators {
+                println!("    user {}: {}", user.username, user.permission);
+            }
+        }
+        if let Some(codeowners) = self.codeowners() {
+            println!("  CODEOWNERS:");
+            for line in codeowners.lines().filter(|line| !line.starts_with('#')) {
+                println!("    {}", line);
+            }
+        }
+
+        if !self.secrets.is_empty() {
+            println!("  Actions secrets:");
+            for secret in &self.secrets {
+                println!("    {} (from {})", secret.name, secret.source);
+            }
+        }
+        if !self.variables.is_empty() {
+            println!("  Actions variables:");
+            for variable in &self.variables {
+                println!("    {} = {}", variable.name, variable.value);
+            }
+        }
+    }
+
+    /// Rejects what only GitHub supports when another provider is configured.
+    pub fn check_provider_support(&self) -> Result<()> {
+        if self.provider == ProviderKind::GitHub {
+            return Ok(());
+        }
+        let unsupported = [
+            ("github_branch_protection", self.branch_protection.is_some()),
+            ("github_teams", !self.teams.is_empty()),
+            ("github_collaborators", !self.collaborators.is_empty()),
+            ("github_secrets", !self.secrets.is_empty()),
+            ("github_variables", !self.variables.is_empty()),
+            (
+                "repository.visibility: internal",
+                self.provider == ProviderKind::Gitea && self.settings.visibility == Visibility::Internal,
+            ),
+        ];
+        let unsupported: Vec<&str> = unsupported.iter().filter(|(_, used)| *used).map(|(name, _)| *name).collect();
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("Not supported with the {} provider: {}", self.provider, unsupported.join(", ")),
+        ))
+    }
+
+    pub fn codeowners(&self) -> Option<String> {
+        github_config::codeowners_content(&self.organization, &self.teams, &self.collaborators)
+    }
+
+    /// Branch the protection applies to, the default branch unless configured otherwise.
+    pub fn protected_branch(&self) -> &str {
+        self.branch_protection
+            .as_ref()
+            .and_then(|protection| protection.branch.as_deref())
+            .unwrap_or_else(|| self.git.default_branch())
+    }
+}
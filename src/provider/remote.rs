@@ -0,0 +1,312 @@
+use std::fmt;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::github_config::IfExists;
+use crate::config::provider_config::ProviderKind;
+use crate::config::{CODEOWNERS_FILE, PULL_REQUEST_BRANCH_PREFIX, REPO_URL};
+use crate::provider::plan::RepositoryPlan;
+use crate::provider::{self, retry, Provider};
+use crate::utils::context;
+
+/// Where the generated code goes, decided before generating.
+pub enum RemoteTarget {
+    /// The repository does not exist and is created.
+    Create,
+    /// The repository exists without any commit, the code is pushed to it.
+    ReuseEmpty { url: String },
+    /// The repository has commits, the code is pushed to a new branch and a pull request
+    /// into `base` is opened.
+    PullRequest { url: String, base: String },
+}
+
+impl fmt::Display for RemoteTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteTarget::Create => write!(f, "create a new repository"),
+            RemoteTarget::ReuseEmpty { url } => write!(f, "push to the existing empty repository {}", url),
+            RemoteTarget::PullRequest { url, base } => {
+                write!(f, "open a pull request into '{}' of the existing repository {}", base, url)
+            }
+        }
+    }
+}
+
+/// Checks whether the planned repository exists and picks the target according to
+/// `repository.if_exists`. Fails when the existing repository cannot be used.
+pub async fn resolve_target(token: &str, plan: &RepositoryPlan) -> Result<RemoteTarget> {
+    let existing = connect(token, plan)
+        .get_repository(&plan.name)
+        .await
+        .map_err(|e| Error::other(format!("Failed to check whether the repository exists: {}", e)))?;
+    let Some(existing) = existing else {
+        return Ok(RemoteTarget::Create);
+    };
+    context::debug_print(&format!(
+        "Repository {} exists (default branch '{}', empty: {})",
+        existing.web_url, existing.default_branch, existing.empty
+    ));
+
+    let full_name = format!("{}/{}", plan.organization, plan.name);
+    match plan.settings.if_exists {
+        IfExists::Abort => Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!(
+                "Repository {} already exists. Choose another name, or set repository.if_exists \
+                 (or --if-exists) to reuse or pull_request",
+                full_name
+            ),
+        )),
+        IfExists::Reuse if !existing.empty => Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!(
+                "Repository {} already exists and has commits, it can only be reused when empty. \
+                 Use pull_request to propose the generated code in a pull request",
+                full_name
+            ),
+        )),
+        IfExists::Reuse => Ok(RemoteTarget::ReuseEmpty { url: existing.web_url }),
+        // An empty repository has no branch to open a pull request into
+        IfExists::PullRequest if existing.empty => Ok(RemoteTarget::ReuseEmpty { url: existing.web_url }),
+        IfExists::PullRequest => Ok(RemoteTarget::PullRequest {
+            url: existing.web_url,
+            base: existing.default_branch,
+        }),
+    }
+}
+
+/// Token given with --token, or the environment variable of the provider (e.g. GITHUB_TOKEN).
+pub fn provider_token(provider: ProviderKind, token: Option<String>) -> Result<String> {
+    token.or_else(|| std::env::var(provider.token_variable()).ok()).ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "{} token is required for remote mode. Set {} env var or use --token",
+                provider,
+                provider.token_variable()
+            ),
+        )
+    })
+}
+
+/// Client of the provider and instance of the plan.
+pub fn connect(token: &str, plan: &RepositoryPlan) -> Box<dyn Provider> {
+    provider::connect(plan.provider, &plan.server, &plan.organization, token)
+}
+
+pub fn extract_organization_from_repo_url() -> Result<String> {
+    // Extract organization from REPO_URL constant
+    // REPO_URL = "https://github.com/NextNodeSolutions"
+    let org_name = REPO_URL
+        .split('/')
+        .next_back()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Could not extract organization from REPO_URL"))?;
+    
+    Ok(org_name.to_string())
+}
+
+/// Writes the CODEOWNERS file built from the teams and collaborators of the plan, if any.
+pub fn write_codeowners(plan: &RepositoryPlan, project_path: &Path) -> Result<()> {
+    let Some(content) = plan.codeowners() else {
+        return Ok(());
+    };
+
+    let codeowners_path = project_path.join(CODEOWNERS_FILE);
+    if codeowners_path.exists() {
+        println!("Replacing the template's {} with the configured code owners", CODEOWNERS_FILE);
+    }
+    if let Some(parent) = codeowners_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&codeowners_path, content)?;
+    println!("Generated {}", CODEOWNERS_FILE);
+    Ok(())
+}
+
+pub async fn create_repository_with_code(
+    token: &str,
+    plan: &RepositoryPlan,
+    target: &RemoteTarget,
+    project_path: &Path,
+) -> Result<()> {
+    let remote = connect(token, plan);
+    let remote = remote.as_ref();
+
+    let repo_url = match target {
+        RemoteTarget::PullRequest { base, .. } => {
+            return open_generation_pull_request(remote, plan, base, project_path).await;
+        }
+        RemoteTarget::Create => {
+            let repo_url = remote
+                .create_repository(&plan.name, &plan.description, &plan.settings)
+                .await
+                .map_err(|e| Error::other(format!("Failed to create {} repository: {}", plan.provider, e)))?;
+            println!("Created {} {} repository: {}", plan.settings.visibility, plan.provider, repo_url);
+            repo_url
+        }
+        RemoteTarget::ReuseEmpty { url } => {
+            println!("Reusing empty {} repository: {}", plan.provider, url);
+            match remote.update_settings(&plan.name, &plan.description, &plan.settings).await {
+                Ok(()) => println!("Applied the {} visibility and repository settings", plan.settings.visibility),
+                Err(e) => eprintln!("Warning: Failed to apply the repository settings: {}", e),
+            }
+            url.clone()
+        }
+    };
+
+    if !plan.topics.is_empty() {
+        println!("Adding topics {} to repository...", plan.topics.join(", "));
+        // Don't fail the entire operation for topic addition failure, just warn
+        match remote.set_topics(&plan.name, &plan.topics).await {
+            Ok(()) => println!("Successfully added {} topic(s) to repository", plan.topics.len()),
+            Err(e) => eprintln!("Warning: Failed to add topics: {}", e),
+        }
+    }
+
+    if !plan.labels.is_empty() {
+        match remote.set_labels(&plan.name, &plan.labels).await {
+            Ok(()) => println!("Successfully set {} label(s) on repository", plan.labels.len()),
+            Err(e) => eprintln!("Warning: Failed to set labels: {}", e),
+        }
+    }
+
+    // Workflows triggered by the first push need their secrets and variables
+    for secret in &plan.secrets {
+        match remote.set_secret(&plan.name, &secret.name, &secret.value).await {
+            Ok(()) => println!("Added secret '{}' from {}", secret.name, secret.source),
+            Err(e) => eprintln!("Warning: Failed to add secret '{}': {}", secret.name, e),
+        }
+    }
+    for variable in &plan.variables {
+        match remote.create_variable(&plan.name, &variable.name, &variable.value).await {
+            Ok(()) => println!("Added variable '{}'", variable.name),
+            Err(e) => eprintln!("Warning: Failed to add variable '{}': {}", variable.name, e),
+        }
+    }
+    
+    // Initialize git and push the generated code (includes pnpm install results)
+    let remote_url = remote.remote_url(&plan.name);
+    if let Err(e) = provider::initialize_git_and_push(remote, project_path, &remote_url, &plan.git) {
+        let error = Error::other(format!("Failed to initialize and push to {}: {}", plan.provider, e));
+        handle_push_failure(remote, plan, target, &repo_url, project_path).await;
+        return Err(error);
+    }
+    
+    println!("Successfully pushed generated code to {} repository!", plan.provider);
+    finish_repository(remote, plan).await;
+    Ok(())
+}
+
+/// Deletes the repository created by this run when configured to, otherwise keeps the
+/// project with a push state so `retry-push` can push it later.
+async fn handle_push_failure(
+    remote: &dyn Provider,
+    plan: &RepositoryPlan,
+    target: &RemoteTarget,
+    repo_url: &str,
+    project_path: &Path,
+) {
+    // A reused repository existed before the run and is never deleted
+    if matches!(target, RemoteTarget::Create) && plan.settings.delete_on_push_failure {
+        match remote.delete_repository(&plan.name).await {
+            Ok(()) => {
+                println!("Deleted the repository {} created by this run", repo_url);
+                return;
+            }
+            Err(e) => eprintln!(
+                "Warning: Failed to delete the repository {} (the token needs the right to delete it): {}",
+                repo_url, e
+            ),
+        }
+    }
+
+    match retry::PushState::write(project_path, repo_url, plan) {
+        Ok(()) => {
+            eprintln!("The repository {} exists but no code was pushed to it.", repo_url);
+            eprintln!("The generated project is kept in '{}', push it again with:", project_path.display());
+            eprintln!("  project-generator retry-push {}", project_path.display());
+        }
+        Err(e) => eprintln!(
+            "Warning: The repository {} was left empty and the push state could not be saved: {}",
+            repo_url, e
+        ),
+    }
+}
+
+/// Setup that needs the pushed branch: default branch, branch protection and access.
+pub async fn finish_repository(remote: &dyn Provider, plan: &RepositoryPlan) {
+    // The default branch can only be changed once it exists
+    if plan.settings.default_branch.is_some() {
+        let branch = plan.git.default_branch();
+        match remote.update_default_branch(&plan.name, branch).await {
+            Ok(()) => println!("Default branch set to '{}'", branch),
+            Err(e) => eprintln!("Warning: Failed to set default branch '{}': {}", branch, e),
+        }
+    }
+
+    if let Some(protection) = &plan.branch_protection {
+        let branch = plan.protected_branch();
+        match remote.protect_branch(&plan.name, branch, protection).await {
+            Ok(()) => {
+                println!("Branch protection applied to '{}':", branch);
+                for rule in protection.describe() {
+                    println!("  - {}", rule);
+                }
+            }
+            Err(e) => eprintln!("Warning: Failed to protect branch '{}', no rule was applied: {}", branch, e),
+        }
+    }
+
+    for team in &plan.teams {
+        match remote.add_team(&plan.name, &team.slug, team.permission).await {
+            Ok(()) => println!("Granted {} access to team '{}'", team.permission, team.slug),
+            Err(e) => eprintln!("Warning: Failed to grant {} access to team '{}': {}", team.permission, team.slug, e),
+        }
+    }
+    for user in &plan.collaborators {
+        match remote.add_collaborator(&plan.name, &user.username, user.permission).await {
+            Ok(true) => println!("Invited '{}' with {} access", user.username, user.permission),
+            Ok(false) => println!("Granted {} access to '{}'", user.permission, user.username),
+            Err(e) => eprintln!("Warning: Failed to grant {} access to '{}': {}", user.permission, user.username, e),
+        }
+    }
+}
+
+/// Pushes the generated code to a new branch of a repository that already has commits and
+/// opens a pull request. The settings, protection, access and Actions configuration of the
+/// existing repository are left as they are.
+async fn open_generation_pull_request(
+    remote: &dyn Provider,
+    plan: &RepositoryPlan,
+    base: &str,
+    project_path: &Path,
+) -> Result<()> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| Error::other(e.to_string()))?
+        .as_secs();
+    let branch = format!("{}{}", PULL_REQUEST_BRANCH_PREFIX, timestamp);
+
+    provider::push_to_new_branch(remote, project_path, &remote.remote_url(&plan.name), base, &branch, &plan.git)
+        .map_err(|e| Error::other(format!("Failed to push the generated code to branch '{}': {}", branch, e)))?;
+    println!("Pushed generated code to branch '{}'", branch);
+
+    let title = match context::get_template_source() {
+        Some(source) => format!("Generate {} from template {}/{}", plan.name, source.category, source.name),
+        None => format!("Generate {}", plan.name),
+    };
+    let mut body = String::from("Code generated by the project generator.\n");
+    if let Some(source) = context::get_template_source() {
+        body.push_str(&format!("\nTemplate: `{}/{}` at commit `{}`\n", source.category, source.name, source.commit));
+    }
+    body.push_str("\nFiles of the repository that the template does not generate are kept.\n");
+
+    let pull_request_url = remote
+        .open_pull_request(&plan.name, &branch, base, &title, &body)
+        .await
+        .map_err(|e| Error::other(format!("Failed to open a pull request from '{}': {}", branch, e)))?;
+    println!("Opened pull request: {}", pull_request_url);
+    println!("The existing repository settings, branch protection, access and Actions configuration were not changed");
+    Ok(())
+}
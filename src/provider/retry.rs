@@ -0,0 +1,99 @@
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::config::PUSH_STATE_FILE;
+use crate::provider::plan::RepositoryPlan;
+use crate::provider::remote::{connect, finish_repository, provider_token};
+use crate::provider;
+use crate::utils::context;
+
+/// What a failed remote push leaves in the kept project for `retry-push`. The secrets and
+/// variables were set before the push and are not saved.
+#[derive(serde::Deserialize)]
+pub struct PushState {
+    pub repository_url: String,
+    pub plan: RepositoryPlan,
+}
+
+impl PushState {
+    pub fn read(project_path: &Path) -> Result<Self> {
+        let state_path = project_path.join(PUSH_STATE_FILE);
+        context::debug_print(&format!("Reading push state: {}", state_path.display()));
+
+        let content = fs::read_to_string(&state_path).map_err(|e| {
+            Error::new(
+                e.kind(),
+                format!(
+                    "Failed to read '{}', is '{}' a project kept after a failed push? {}",
+                    state_path.display(),
+                    project_path.display(),
+                    e
+                ),
+            )
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("Invalid push state: {}", e)))
+    }
+
+    pub fn write(project_path: &Path, repository_url: &str, plan: &RepositoryPlan) -> Result<()> {
+        let state_path = project_path.join(PUSH_STATE_FILE);
+        context::debug_print(&format!("Writing push state: {}", state_path.display()));
+
+        let state = json!({ "repository_url": repository_url, "plan": plan });
+        let mut content = serde_json::to_string_pretty(&state)?;
+        content.push('\n');
+        fs::write(state_path, content)
+    }
+}
+
+/// Whether the project was kept after a failed push.
+pub fn has_pending_push(project_path: &Path) -> bool {
+    project_path.join(PUSH_STATE_FILE).is_file()
+}
+
+/// Pushes a project kept after a failed push to its repository, then applies the setup that
+/// needs the pushed branch. The project directory is left in place.
+pub async fn handle_retry_push(project_dir: &Path, token: Option<String>) -> Result<()> {
+    let state = PushState::read(project_dir)?;
+    let plan = &state.plan;
+    let token = provider_token(plan.provider, token)?;
+    let token = token.as_str();
+    let remote = connect(token, plan);
+    let remote = remote.as_ref();
+
+    let existing = remote
+        .get_repository(&plan.name)
+        .await
+        .map_err(|e| Error::other(format!("Failed to check the repository: {}", e)))?;
+    match existing {
+        None => {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("Repository {} no longer exists, run remote mode again", state.repository_url),
+            ))
+        }
+        Some(existing) if !existing.empty => {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("Repository {} already has commits, there is nothing to retry", state.repository_url),
+            ))
+        }
+        Some(_) => {}
+    }
+
+    // The state file is not part of the project and must not be pushed
+    fs::remove_file(project_dir.join(PUSH_STATE_FILE))?;
+    let remote_url = remote.remote_url(&plan.name);
+    if let Err(e) = provider::initialize_git_and_push(remote, project_dir, &remote_url, &plan.git) {
+        PushState::write(project_dir, &state.repository_url, plan)?;
+        return Err(Error::other(format!("Failed to initialize and push to {}: {}", plan.provider, e)));
+    }
+    println!("Successfully pushed generated code to {}", state.repository_url);
+
+    finish_repository(remote, plan).await;
+    println!("The project directory '{}' can now be deleted", project_dir.display());
+    Ok(())
+}
@@ -6,6 +6,18 @@ use crate::config::{REPO_URL, TEMPLATE_REPO_URL, TEMPLATE_BRANCH, TEMPLATE_CATEG
 
 pub struct TemplateManager {
     repo_path: PathBuf,
+    branch: String,
+    commit: String,
+}
+
+/// Where a template comes from, recorded in the generation manifest.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct TemplateSource {
+    pub repository: String,
+    pub branch: String,
+    pub commit: String,
+    pub category: String,
+    pub name: String,
 }
 
 impl TemplateManager {
@@ -49,14 +61,24 @@ impl TemplateManager {
         let branch_to_use = branch.unwrap_or(TEMPLATE_BRANCH);
         builder.branch(branch_to_use);
 
-        builder.clone(format!("{}{}", REPO_URL, TEMPLATE_REPO_URL).as_str(), &repo_path).map_err(|e| {
-            std::io::Error::new(
-                std::io::ErrorKind::Other,
+        let repo = builder.clone(format!("{}{}", REPO_URL, TEMPLATE_REPO_URL).as_str(), &repo_path).map_err(|e| {
+            std::io::Error::other(
                 format!("Failed to clone repository: {}", e),
             )
         })?;
 
-        Ok(Self { repo_path })
+        // Resolve the exact commit so generated projects can record it
+        let commit = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map(|commit| commit.id().to_string())
+            .map_err(|e| std::io::Error::other(format!("Failed to resolve template commit: {}", e)))?;
+
+        Ok(Self {
+            repo_path,
+            branch: branch_to_use.to_string(),
+            commit,
+        })
     }
 
     pub fn list_templates(&self) -> std::io::Result<Vec<(String, String)>> {
@@ -86,6 +108,45 @@ impl TemplateManager {
     pub fn get_template_path(&self, category: &str, template: &str) -> PathBuf {
         self.repo_path.join(category).join(template)
     }
+
+    /// Checks out a branch, tag or commit of the template repository and returns the resolved commit.
+    pub fn checkout(&mut self, reference: &str) -> std::io::Result<String> {
+        let repo = git2::Repository::open(&self.repo_path)
+            .map_err(|e| std::io::Error::other(format!("Failed to open template repository: {}", e)))?;
+
+        // Branches other than the cloned one only exist as remote-tracking references
+        let object = repo
+            .revparse_single(reference)
+            .or_else(|_| repo.revparse_single(&format!("origin/{}", reference)))
+            .map_err(|e| std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Template reference '{}' not found: {}", reference, e),
+            ))?;
+        let commit = object
+            .peel_to_commit()
+            .map_err(|e| std::io::Error::other(format!("'{}' does not point to a commit: {}", reference, e)))?;
+
+        repo.checkout_tree(commit.as_object(), Some(git2::build::CheckoutBuilder::new().force()))
+            .and_then(|_| repo.set_head_detached(commit.id()))
+            .map_err(|e| std::io::Error::other(format!("Failed to check out '{}': {}", reference, e)))?;
+
+        self.commit = commit.id().to_string();
+        // Keep the recorded branch when checking out a bare commit SHA
+        if !self.commit.starts_with(reference) {
+            self.branch = reference.to_string();
+        }
+        Ok(self.commit.clone())
+    }
+
+    pub fn get_source(&self, category: &str, template: &str) -> TemplateSource {
+        TemplateSource {
+            repository: format!("{}{}", REPO_URL, TEMPLATE_REPO_URL),
+            branch: self.branch.clone(),
+            commit: self.commit.clone(),
+            category: category.to_string(),
+            name: template.to_string(),
+        }
+    }
 }
 
 impl Drop for TemplateManager {
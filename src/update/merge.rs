@@ -0,0 +1,129 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Result;
+use std::path::Path;
+
+use crate::config::MANIFEST_FILE;
+use crate::generate::manifest::Manifest;
+use crate::utils::context;
+
+/// What happened to each file during a three-way merge.
+#[derive(Default)]
+pub struct MergeSummary {
+    pub updated: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub merged: Vec<String>,
+    pub conflicts: Vec<String>,
+    pub kept: Vec<String>,
+}
+
+impl MergeSummary {
+    pub fn print(&self) {
+        print_group("Updated", &self.updated);
+        print_group("Added", &self.added);
+        print_group("Removed", &self.removed);
+        print_group("Merged", &self.merged);
+        print_group("Kept local version", &self.kept);
+        print_group("CONFLICTS", &self.conflicts);
+    }
+}
+
+fn print_group(title: &str, files: &[String]) {
+    if files.is_empty() {
+        return;
+    }
+    println!("{} ({}):", title, files.len());
+    for file in files {
+        println!("  {}", file);
+    }
+}
+
+/// Applies the changes between the `base` and `target` renders to the project.
+///
+/// Files the user did not touch are replaced, edited files are merged line by line
+/// and overlapping edits are left with conflict markers.
+pub fn merge_trees(base_dir: &Path, target_dir: &Path, project_dir: &Path) -> Result<MergeSummary> {
+    let base_files = Manifest::read(base_dir)?.files;
+    let target_files = Manifest::read(target_dir)?.files;
+    let paths: BTreeSet<&String> = base_files.keys().chain(target_files.keys()).collect();
+
+    let mut summary = MergeSummary::default();
+    for relative in paths {
+        if relative == MANIFEST_FILE {
+            continue;
+        }
+        if base_files.get(relative) == target_files.get(relative) {
+            continue;
+        }
+
+        let base = read_optional(&base_dir.join(relative))?;
+        let target = read_optional(&target_dir.join(relative))?;
+        let project_path = project_dir.join(relative);
+        let ours = read_optional(&project_path)?;
+        context::debug_print(&format!("Merging template change in: {}", relative));
+
+        match (base, target, ours) {
+            // Removed from the template
+            (Some(base), None, Some(ours)) => {
+                if ours == base {
+                    fs::remove_file(&project_path)?;
+                    summary.removed.push(relative.clone());
+                } else {
+                    summary.kept.push(format!("{} (removed from template, modified locally)", relative));
+                }
+            }
+            (_, None, _) => {}
+            // Deleted locally, the user's choice wins
+            (Some(_), Some(_), None) => {
+                summary.kept.push(format!("{} (deleted locally)", relative));
+            }
+            (None, Some(_), None) => {
+                copy_file(&target_dir.join(relative), &project_path)?;
+                summary.added.push(relative.clone());
+            }
+            (base, Some(target), Some(ours)) => {
+                if ours == target {
+                    continue;
+                }
+                if base.as_ref() == Some(&ours) {
+                    copy_file(&target_dir.join(relative), &project_path)?;
+                    summary.updated.push(relative.clone());
+                    continue;
+                }
+
+                let base = base.unwrap_or_default();
+                match (String::from_utf8(base), String::from_utf8(ours), String::from_utf8(target)) {
+                    (Ok(base), Ok(ours), Ok(target)) => match diffy::merge(&base, &ours, &target) {
+                        Ok(merged) => {
+                            fs::write(&project_path, merged)?;
+                            summary.merged.push(relative.clone());
+                        }
+                        Err(conflicted) => {
+                            fs::write(&project_path, conflicted)?;
+                            summary.conflicts.push(relative.clone());
+                        }
+                    },
+                    _ => summary.conflicts.push(format!("{} (binary file changed in template and locally, kept local version)", relative)),
+                }
+            }
+        }
+    }
+    Ok(summary)
+}
+
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn copy_file(src: &Path, dst: &Path) -> Result<()> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::copy(src, dst)?;
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use git2::{Repository, StatusOptions};
+
+mod merge;
+mod regenerate;
+
+pub use regenerate::handle_regenerate;
+
+use crate::cli::prompt_for_variable;
+use crate::config::MANIFEST_FILE;
+use crate::generate::manifest::Manifest;
+use crate::generate::project_generator;
+use crate::template::{TemplateManager, TemplateSource};
+use crate::utils::{context, strings};
+
+/// Updates a generated project to another template version.
+///
+/// The project is rendered twice from its recorded variables, once at the recorded
+/// template commit and once at the requested reference, and the difference between
+/// both renders is merged into the working tree.
+pub fn handle_update(project_dir: &Path, reference: Option<&str>) -> Result<()> {
+    let manifest = Manifest::read(project_dir)?;
+    let source = manifest.template.clone().ok_or_else(|| {
+        Error::new(ErrorKind::InvalidData, "The generation manifest does not record a template source")
+    })?;
+
+    ensure_clean_worktree(project_dir)?;
+
+    // Renders are only compared, hooks and git would change them and run twice for nothing
+    let mut options = context::get_options();
+    options.no_hooks = true;
+    options.git_init = Some(false);
+    context::set_options(options);
+
+    let target_reference = reference.unwrap_or(&source.branch).to_string();
+    println!(
+        "Updating '{}' from template '{}/{}' ({} -> {})",
+        project_dir.display(),
+        source.category,
+        source.name,
+        short_sha(&source.commit),
+        target_reference
+    );
+
+    let work_dir = std::env::temp_dir().join(format!("project-generator-update-{}", timestamp()));
+    let result = update_in(&work_dir, project_dir, &manifest, &source, &target_reference);
+
+    if let Err(e) = fs::remove_dir_all(&work_dir) {
+        context::debug_print(&format!("Failed to remove update work directory '{}': {}", work_dir.display(), e));
+    }
+    result
+}
+
+fn update_in(
+    work_dir: &Path,
+    project_dir: &Path,
+    manifest: &Manifest,
+    source: &TemplateSource,
+    target_reference: &str,
+) -> Result<()> {
+    let mut template_manager = TemplateManager::new(Some(&source.branch))?;
+    let variables = complete_variables(&template_manager, source, &manifest.variables)?;
+
+    let base_dir = work_dir.join("base");
+    template_manager.checkout(&source.commit)?;
+    render(&template_manager, source, &variables, &base_dir)?;
+
+    let target_commit = template_manager.checkout(target_reference)?;
+    if target_commit == source.commit {
+        println!("Project is already up to date with '{}' ({})", target_reference, short_sha(&target_commit));
+        return Ok(());
+    }
+
+    let target_dir = work_dir.join("target");
+    render(&template_manager, source, &variables, &target_dir)?;
+
+    let summary = merge::merge_trees(&base_dir, &target_dir, project_dir)?;
+
+    // The manifest of the new render records the new template commit and file hashes
+    fs::copy(target_dir.join(MANIFEST_FILE), project_dir.join(MANIFEST_FILE))?;
+
+    summary.print();
+    println!(
+        "Project updated to '{}' ({}). Review the changes with git before committing.",
+        target_reference,
+        short_sha(&target_commit)
+    );
+    if !summary.conflicts.is_empty() {
+        println!("Resolve the conflict markers in the files listed above, then install dependencies again if needed.");
+    }
+    Ok(())
+}
+
+/// Renders the checked out template with the given variables, without prompting.
+pub fn render(
+    template_manager: &TemplateManager,
+    source: &TemplateSource,
+    variables: &HashMap<String, String>,
+    output: &Path,
+) -> Result<()> {
+    let template_path = template_manager.get_template_path(&source.category, &source.name);
+    context::set_variables(variables.clone());
+    context::set_template_source(template_manager.get_source(&source.category, &source.name));
+
+    context::debug_print(&format!("Rendering template into '{}'", output.display()));
+    project_generator::generate_project(&template_path, output, false)
+}
+
+/// Returns the recorded variables, prompting for those the template needs but the manifest
+/// does not contain (secret variables are never recorded).
+pub fn complete_variables(
+    template_manager: &TemplateManager,
+    source: &TemplateSource,
+    recorded: &BTreeMap<String, String>,
+) -> Result<HashMap<String, String>> {
+    let mut variables: HashMap<String, String> = recorded.clone().into_iter().collect();
+
+    for key in missing_variables(template_manager, source, &variables) {
+        let value = prompt_for_variable(&key).ok_or_else(|| {
+            Error::new(ErrorKind::InvalidInput, format!("An error occurred while entering {}", key))
+        })?;
+        variables.insert(key, value);
+    }
+    Ok(variables)
+}
+
+/// Lists the variables used by the template that have no value.
+pub fn missing_variables(
+    template_manager: &TemplateManager,
+    source: &TemplateSource,
+    variables: &HashMap<String, String>,
+) -> Vec<String> {
+    let template_path = template_manager.get_template_path(&source.category, &source.name);
+    let mut missing: Vec<String> = strings::extract_unique_keys(&template_path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|key| !variables.contains_key(key))
+        .collect();
+    missing.sort();
+    missing
+}
+
+/// Refuses to touch a project whose changes are not committed, so the update can be reviewed and reverted.
+fn ensure_clean_worktree(project_dir: &Path) -> Result<()> {
+    let repo = Repository::discover(project_dir).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("'{}' is not inside a git repository. Commit the project to git before updating it.", project_dir.display()),
+        )
+    })?;
+
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false).recurse_untracked_dirs(true);
+
+    // In a monorepo, changes outside the project do not matter
+    let relative = match (repo.workdir(), project_dir.canonicalize()) {
+        (Some(workdir), Ok(project_dir)) => workdir
+            .canonicalize()
+            .ok()
+            .and_then(|workdir| project_dir.strip_prefix(workdir).ok().map(Path::to_path_buf)),
+        _ => None,
+    };
+    if let Some(relative) = relative.filter(|relative| !relative.as_os_str().is_empty()) {
+        let pathspec = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        context::debug_print(&format!("Checking git status under '{}'", pathspec));
+        options.pathspec(pathspec);
+    }
+
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| Error::other(format!("Failed to read git status: {}", e)))?;
+
+    let dirty: Vec<String> = statuses
+        .iter()
+        .filter_map(|entry| entry.path().map(|p| p.to_string()))
+        .collect();
+
+    if !dirty.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "The project has uncommitted changes, commit or stash them before updating:\n  {}",
+                dirty.join("\n  ")
+            ),
+        ));
+    }
+    Ok(())
+}
+
+pub fn short_sha(commit: &str) -> &str {
+    &commit[..commit.len().min(7)]
+}
+
+fn timestamp() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis()
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path, PathBuf};
+
+use crate::generate::manifest::Manifest;
+use crate::template::TemplateManager;
+use crate::utils::context;
+
+use super::{missing_variables, render, short_sha};
+
+/// Generates a new project from the manifest of an existing one, without prompting.
+///
+/// The recorded template commit and variables are used unless overridden, so the
+/// output can be compared across template versions.
+pub fn handle_regenerate(
+    project_dir: &Path,
+    output: Option<&Path>,
+    reference: Option<&str>,
+    overrides: &[(String, String)],
+) -> Result<()> {
+    let manifest = Manifest::read(project_dir)?;
+    let source = manifest.template.clone().ok_or_else(|| {
+        Error::new(ErrorKind::InvalidData, "The generation manifest does not record a template source")
+    })?;
+
+    let output = match output {
+        Some(output) => output.to_path_buf(),
+        None => default_output(project_dir)?,
+    };
+    let reference = reference.unwrap_or(&source.commit);
+
+    // Nothing may prompt: hooks would ask for trust, and the output is only compared
+    let mut options = context::get_options();
+    options.no_hooks = true;
+    context::set_options(options);
+
+    let mut template_manager = TemplateManager::new(Some(&source.branch))?;
+    let commit = template_manager.checkout(reference)?;
+
+    let mut variables: HashMap<String, String> = manifest.variables.into_iter().collect();
+    variables.extend(overrides.iter().cloned());
+
+    let missing = missing_variables(&template_manager, &source, &variables);
+    if !missing.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "No value recorded for: {}. Provide them with --set <name>=<value>",
+                missing.join(", ")
+            ),
+        ));
+    }
+
+    println!(
+        "Regenerating '{}' from template '{}/{}' at {} into '{}'",
+        project_dir.display(),
+        source.category,
+        source.name,
+        short_sha(&commit),
+        output.display()
+    );
+    render(&template_manager, &source, &variables, &output)?;
+
+    println!("Project regenerated successfully in '{}'", output.display());
+    Ok(())
+}
+
+/// `<project-dir>-regenerated` next to the project. The path is resolved first, so `.` or a
+/// path ending in `..` names the actual directory instead of nesting the output inside it.
+fn default_output(project_dir: &Path) -> Result<PathBuf> {
+    let project_dir = project_dir.canonicalize()?;
+    let name = project_dir.file_name().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Cannot derive an output directory from '{}', use --output", project_dir.display()),
+        )
+    })?;
+    Ok(project_dir.with_file_name(format!("{}-regenerated", name.to_string_lossy())))
+}
@@ -0,0 +1,129 @@
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::utils::context;
+
+/// Builds a command running `command_line` through the platform shell.
+pub fn shell_command(command_line: &str) -> Command {
+    if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(command_line);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(command_line);
+        command
+    }
+}
+
+/// Output of a command run by `run_captured`.
+pub struct CapturedOutput {
+    /// `None` when the command was killed after timing out.
+    pub status: Option<ExitStatus>,
+    /// Lines written to stdout and stderr, in the order they were read.
+    pub lines: Vec<String>,
+}
+
+impl CapturedOutput {
+    pub fn success(&self) -> bool {
+        self.status.is_some_and(|status| status.success())
+    }
+}
+
+/// How long output is still collected after the command exited, in case it left
+/// background processes holding the pipes open.
+const OUTPUT_GRACE_PERIOD: Duration = Duration::from_secs(1);
+
+/// Runs a command with captured stdout and stderr, killing it after `timeout`.
+pub fn run_captured(command: &mut Command, timeout: Duration) -> io::Result<CapturedOutput> {
+    run_captured_with(command, timeout, |_, _| {})
+}
+
+/// Like `run_captured`, calling `on_progress` regularly with the lines read since the
+/// previous call and the time elapsed, to echo output or animate progress.
+pub fn run_captured_with(
+    command: &mut Command,
+    timeout: Duration,
+    mut on_progress: impl FnMut(&[String], Duration),
+) -> io::Result<CapturedOutput> {
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // A process group of its own lets a timeout kill the whole shell pipeline
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    let mut child = command.spawn()?;
+
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let (done_sender, done_receiver) = mpsc::channel();
+    let mut readers = 0;
+    if let Some(stdout) = child.stdout.take() {
+        spawn_reader(stdout, Arc::clone(&lines), done_sender.clone());
+        readers += 1;
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_reader(stderr, Arc::clone(&lines), done_sender.clone());
+        readers += 1;
+    }
+
+    let started = Instant::now();
+    let mut reported = 0;
+    let mut report = |lines: &Mutex<Vec<String>>| {
+        let lines = lines.lock().unwrap();
+        on_progress(&lines[reported..], started.elapsed());
+        reported = lines.len();
+    };
+
+    let status = loop {
+        report(&lines);
+        if let Some(status) = child.try_wait()? {
+            break Some(status);
+        }
+        if started.elapsed() >= timeout {
+            context::debug_print(&format!("Command timed out after {}s, killing it", timeout.as_secs()));
+            kill(&mut child)?;
+            child.wait()?;
+            break None;
+        }
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    let deadline = Instant::now() + OUTPUT_GRACE_PERIOD;
+    for _ in 0..readers {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if done_receiver.recv_timeout(remaining).is_err() {
+            break;
+        }
+    }
+    report(&lines);
+    let lines = std::mem::take(&mut *lines.lock().unwrap());
+    Ok(CapturedOutput { status, lines })
+}
+
+fn spawn_reader<R: Read + Send + 'static>(stream: R, lines: Arc<Mutex<Vec<String>>>, done: mpsc::Sender<()>) {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines().map_while(Result::ok) {
+            lines.lock().unwrap().push(line);
+        }
+        let _ = done.send(());
+    });
+}
+
+#[cfg(unix)]
+fn kill(child: &mut Child) -> io::Result<()> {
+    // The negative pid targets the process group created in `run_captured`
+    let result = unsafe { libc::kill(-(child.id() as i32), libc::SIGKILL) };
+    if result != 0 {
+        return child.kill();
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn kill(child: &mut Child) -> io::Result<()> {
+    child.kill()
+}
@@ -1,9 +1,63 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::config::file_config::HookEnvironment;
+use crate::config::GitSettings;
+use crate::template::TemplateSource;
+
+/// What to do with files that already exist at the project path.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Refuse to generate into a non-empty directory.
+    #[default]
+    Refuse,
+    /// Overwrite existing files with the generated ones.
+    Force,
+    /// Keep existing files and only add new ones.
+    SkipExisting,
+    /// Show a diff and ask for each conflicting file.
+    Prompt,
+}
+
+/// Options controlling how a project is generated, set once from the CLI arguments.
+#[derive(Clone, Debug, Default)]
+pub struct GenerationOptions {
+    /// Keep the staging directory on failure instead of deleting it.
+    pub keep_staging: bool,
+    /// How to handle files already present at the project path.
+    pub overwrite_policy: OverwritePolicy,
+    /// Fail generation on unresolved placeholders, missing variables and unused variables.
+    pub strict: bool,
+    /// Never install dependencies after generation.
+    pub skip_install: bool,
+    /// Install command overriding the template and the detected package manager.
+    pub install_command: Option<String>,
+    /// Installation timeout in seconds, `DEFAULT_INSTALL_TIMEOUT_SECS` when unset and no limit when 0.
+    pub install_timeout: Option<u64>,
+    /// Write installation output to a log file instead of printing it.
+    pub quiet: bool,
+    /// Log file for the installation output.
+    pub install_log: Option<PathBuf>,
+    /// Never run the hooks declared by templates.
+    pub no_hooks: bool,
+    /// Run template hooks without asking, even from untrusted templates.
+    pub trust_hooks: bool,
+    /// Environment given to template hooks, from the configuration file.
+    pub hook_environment: HookEnvironment,
+    /// Initialize a git repository in the generated project, overriding the configuration
+    /// file and the template when set.
+    pub git_init: Option<bool>,
+    /// Git settings from the configuration file.
+    pub git: GitSettings,
+}
 
 thread_local! {
     static VARIABLES: RefCell<HashMap<String, String>> = RefCell::new(HashMap::new());
-    static DEBUG_MODE: RefCell<bool> = RefCell::new(false);
+    static DEBUG_MODE: RefCell<bool> = const { RefCell::new(false) };
+    static OPTIONS: RefCell<GenerationOptions> = RefCell::new(GenerationOptions::default());
+    static ISSUES: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    static TEMPLATE_SOURCE: RefCell<Option<TemplateSource>> = const { RefCell::new(None) };
 }
 
 pub fn set_variables(vars: HashMap<String, String>) {
@@ -14,6 +68,10 @@ pub fn get_variable(key: &str) -> Option<String> {
     VARIABLES.with(|v| v.borrow().get(key).cloned())
 }
 
+pub fn get_variable_names() -> Vec<String> {
+    VARIABLES.with(|v| v.borrow().keys().cloned().collect())
+}
+
 pub fn set_debug_mode(debug: bool) {
     DEBUG_MODE.with(|d| *d.borrow_mut() = debug);
 }
@@ -22,6 +80,32 @@ pub fn is_debug_mode() -> bool {
     DEBUG_MODE.with(|d| *d.borrow())
 }
 
+pub fn set_options(options: GenerationOptions) {
+    OPTIONS.with(|o| *o.borrow_mut() = options);
+}
+
+pub fn get_options() -> GenerationOptions {
+    OPTIONS.with(|o| o.borrow().clone())
+}
+
+pub fn set_template_source(source: TemplateSource) {
+    TEMPLATE_SOURCE.with(|t| *t.borrow_mut() = Some(source));
+}
+
+pub fn get_template_source() -> Option<TemplateSource> {
+    TEMPLATE_SOURCE.with(|t| t.borrow().clone())
+}
+
+/// Records a replacement problem, collected into the strict mode report.
+pub fn report_issue(message: &str) {
+    debug_print(&format!("Warning: {}", message));
+    ISSUES.with(|i| i.borrow_mut().push(message.to_string()));
+}
+
+pub fn take_issues() -> Vec<String> {
+    ISSUES.with(|i| std::mem::take(&mut *i.borrow_mut()))
+}
+
 pub fn debug_print(message: &str) {
     if is_debug_mode() {
         println!("[DEBUG] {}", message);
@@ -1,4 +1,6 @@
+pub mod command;
 pub mod context;
 pub mod error;
+pub mod progress;
 pub mod strings;
 pub mod validation;
@@ -0,0 +1,46 @@
+use std::io::{self, Write};
+use std::time::Duration;
+
+const FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+/// Longest part of the last output line shown next to the spinner.
+const MAX_LINE_WIDTH: usize = 60;
+
+/// Single-line progress display for long commands in interactive terminals.
+pub struct Spinner {
+    message: String,
+    frame: usize,
+    last_line: String,
+}
+
+impl Spinner {
+    pub fn new(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            frame: 0,
+            last_line: String::new(),
+        }
+    }
+
+    /// Redraws the spinner with the elapsed time and the last line of output.
+    pub fn tick(&mut self, new_lines: &[String], elapsed: Duration) {
+        if let Some(line) = new_lines.iter().rev().find(|line| !line.trim().is_empty()) {
+            self.last_line = line.chars().filter(|c| !c.is_control()).take(MAX_LINE_WIDTH).collect();
+        }
+        self.frame = (self.frame + 1) % FRAMES.len();
+
+        print!(
+            "\r\x1b[2K{} {} ({}s) {}",
+            FRAMES[self.frame],
+            self.message,
+            elapsed.as_secs(),
+            self.last_line.trim()
+        );
+        let _ = io::stdout().flush();
+    }
+
+    /// Clears the spinner line.
+    pub fn finish(&self) {
+        print!("\r\x1b[2K");
+        let _ = io::stdout().flush();
+    }
+}
@@ -1,4 +1,4 @@
-use crate::config::TemplateJson;
+use crate::config::{TemplateJson, TemplateMetadata, TEMPLATE_METADATA_FILE};
 use std::path::Path;
 
 pub fn read_template_config(template_path: &Path) -> std::io::Result<TemplateJson> {
@@ -8,6 +8,17 @@ pub fn read_template_config(template_path: &Path) -> std::io::Result<TemplateJso
     Ok(config)
 }
 
+/// Reads the optional template metadata, returning defaults when the template has none.
+pub fn read_template_metadata(template_path: &Path) -> std::io::Result<TemplateMetadata> {
+    let metadata_path = template_path.join(TEMPLATE_METADATA_FILE);
+    if !metadata_path.exists() {
+        return Ok(TemplateMetadata::default());
+    }
+    let content = std::fs::read_to_string(metadata_path)?;
+    let metadata: TemplateMetadata = serde_json::from_str(&content)?;
+    Ok(metadata)
+}
+
 pub fn extract_unique_keys(template_path: &Path) -> std::io::Result<Vec<String>> {
     let config = read_template_config(template_path)?;
     let mut keys = std::collections::HashSet::new();